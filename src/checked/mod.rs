@@ -0,0 +1,75 @@
+use std::fmt::Display;
+use std::ops::{Add, Div, Mul, Sub};
+
+/// Integer arithmetic for the solvers. With the `checked-arithmetic` feature enabled, every operation is checked and
+/// panics with the name of the operation (e.g. `day13 cost`) and its operands on overflow, instead of
+/// silently wrapping in release builds.
+pub trait Integer: Copy + Display + Add<Output=Self> + Sub<Output=Self> + Mul<Output=Self> + Div<Output=Self> {
+    const ZERO: Self;
+
+    fn checked_add(self, rhs: Self) -> Option<Self>;
+    fn checked_sub(self, rhs: Self) -> Option<Self>;
+    fn checked_mul(self, rhs: Self) -> Option<Self>;
+    fn checked_div(self, rhs: Self) -> Option<Self>;
+}
+
+macro_rules! impl_integer {
+    ($($t:ty),*) => {
+        $(
+            impl Integer for $t {
+                const ZERO: Self = 0;
+
+                fn checked_add(self, rhs: Self) -> Option<Self> { <$t>::checked_add(self, rhs) }
+                fn checked_sub(self, rhs: Self) -> Option<Self> { <$t>::checked_sub(self, rhs) }
+                fn checked_mul(self, rhs: Self) -> Option<Self> { <$t>::checked_mul(self, rhs) }
+                fn checked_div(self, rhs: Self) -> Option<Self> { <$t>::checked_div(self, rhs) }
+            }
+        )*
+    };
+}
+
+impl_integer!(i32, i64, u32, u64, usize);
+
+const IS_CHECKED: bool = cfg!(feature = "checked-arithmetic");
+
+pub fn add<T: Integer>(left: T, right: T, operation: &str) -> T {
+    if IS_CHECKED {
+        left.checked_add(right).unwrap_or_else(|| overflow(operation, left, "+", right))
+    } else {
+        left + right
+    }
+}
+
+pub fn sub<T: Integer>(left: T, right: T, operation: &str) -> T {
+    if IS_CHECKED {
+        left.checked_sub(right).unwrap_or_else(|| overflow(operation, left, "-", right))
+    } else {
+        left - right
+    }
+}
+
+pub fn mul<T: Integer>(left: T, right: T, operation: &str) -> T {
+    if IS_CHECKED {
+        left.checked_mul(right).unwrap_or_else(|| overflow(operation, left, "*", right))
+    } else {
+        left * right
+    }
+}
+
+/// Division by zero is reported like an overflow.
+pub fn div<T: Integer>(left: T, right: T, operation: &str) -> T {
+    if IS_CHECKED {
+        left.checked_div(right).unwrap_or_else(|| overflow(operation, left, "/", right))
+    } else {
+        left / right
+    }
+}
+
+pub fn sum<T: Integer>(numbers: impl Iterator<Item=T>, operation: &str) -> T {
+    numbers.fold(T::ZERO, |sum, number| add(sum, number, operation))
+}
+
+#[cold]
+fn overflow(operation: &str, left: impl Display, operator: &str, right: impl Display) -> ! {
+    panic!("arithmetic overflow in {}: {} {} {}", operation, left, operator, right)
+}
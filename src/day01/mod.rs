@@ -1,49 +1,128 @@
 use std::collections::HashMap;
 use std::iter::zip;
+use std::str::FromStr;
+
+use crate::runner::{self, Run};
+
+pub mod streaming;
 
 #[allow(dead_code)]
 pub fn main() {
     let input = include_str!("input.txt");
 
-    println!("PART 1: {}", part1(input));
-    println!("PART 2: {}", part2(input));
+    runner::run(input, solve).print();
 }
 
-fn part1(input: &str) -> i32 {
-    let (mut list1, mut list2) = parse_input_into_lists(input);
+pub fn solve(input: &str, run: &mut Run) {
+    let lists = run.parse(|| Lists::from_str(input).unwrap());
+
+    run.part(|| part1(&lists));
+    run.part(|| part2(&lists));
+}
 
-    list1.sort();
-    list2.sort();
+fn part1(lists: &Lists) -> i32 {
+    lists.calculate_distance(0, 1)
+}
 
-    zip(list1, list2)
-        .map(|(a, b)| (a - b).abs())
-        .sum()
+fn part2(lists: &Lists) -> i32 {
+    lists.calculate_similarity_score(0, 1)
 }
 
-fn part2(input: &str) -> i32 {
-    let (list1, list2) = parse_input_into_lists(input);
+/// Prints the distance and similarity score of every pair of columns.
+pub fn explain(input: &str) {
+    let lists = Lists::from_str(input).unwrap();
 
-    let list2_number_counts = list2.into_iter()
-        .fold(HashMap::new(), |mut map, number| {
-            *map.entry(number).or_insert(0) += 1;
-            map
-        });
+    lists.calculate_pair_metrics().iter().for_each(|metrics| println!(
+        "COLUMNS {} AND {}: distance {}, similarity score {}",
+        metrics.column + 1, metrics.other_column + 1, metrics.distance, metrics.similarity_score
+    ));
+}
 
-    list1
-        .iter()
-        .map(|number| number * list2_number_counts.get(number).unwrap_or(&0))
-        .sum()
+struct Lists {
+    columns: Vec<Vec<i32>>,
 }
 
-fn parse_input_into_lists(input: &str) -> (Vec<i32>, Vec<i32>) {
-    input
-        .lines()
-        .map(|line| {
-            let mut numbers = line
+impl Lists {
+    fn calculate_distance(&self, column: usize, other_column: usize) -> i32 {
+        let mut list1 = self.columns[column].clone();
+        let mut list2 = self.columns[other_column].clone();
+
+        list1.sort();
+        list2.sort();
+
+        zip(list1, list2)
+            .map(|(a, b)| (a - b).abs())
+            .sum()
+    }
+
+    /// Sums up each number of `column`, multiplied by how often it appears in `other_column`.
+    fn calculate_similarity_score(&self, column: usize, other_column: usize) -> i32 {
+        let other_column_number_counts = self.columns[other_column].iter()
+            .fold(HashMap::new(), |mut map, number| {
+                *map.entry(number).or_insert(0) += 1;
+                map
+            });
+
+        self.columns[column]
+            .iter()
+            .map(|number| number * other_column_number_counts.get(number).unwrap_or(&0))
+            .sum()
+    }
+
+    fn calculate_pair_metrics(&self) -> Vec<PairMetrics> {
+        (0..self.columns.len())
+            .flat_map(|column| ((column + 1)..self.columns.len()).map(move |other_column| (column, other_column)))
+            .map(|(column, other_column)| PairMetrics {
+                column,
+                other_column,
+                distance: self.calculate_distance(column, other_column),
+                similarity_score: self.calculate_similarity_score(column, other_column),
+            })
+            .collect()
+    }
+}
+
+impl FromStr for Lists {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut columns: Vec<Vec<i32>> = vec![];
+
+        for (line_index, line) in s.lines().enumerate().filter(|(_, line)| !line.trim().is_empty()) {
+            let line_number = line_index + 1;
+
+            let numbers = line
                 .split_whitespace()
-                .map(|number| number.parse::<i32>().unwrap_or_default());
+                .map(|number| number.parse::<i32>()
+                    .map_err(|_| format!("line {}: invalid number '{}'", line_number, number)))
+                .collect::<Result<Vec<i32>, String>>()?;
+
+            if columns.is_empty() {
+                if numbers.len() < 2 {
+                    return Err(format!("line {}: expected at least 2 columns, found {}", line_number, numbers.len()));
+                }
+
+                columns = vec![vec![]; numbers.len()];
+            }
+
+            if numbers.len() != columns.len() {
+                return Err(format!("line {}: expected {} columns, found {}", line_number, columns.len(), numbers.len()));
+            }
+
+            zip(columns.iter_mut(), numbers).for_each(|(column, number)| column.push(number));
+        }
+
+        if columns.is_empty() {
+            return Err(String::from("no rows"));
+        }
+
+        Ok(Lists { columns })
+    }
+}
 
-            (numbers.next().unwrap_or_default(), numbers.next().unwrap_or_default())
-        })
-        .unzip()
+struct PairMetrics {
+    column: usize,
+    other_column: usize,
+    distance: i32,
+    similarity_score: i32,
 }
@@ -0,0 +1,290 @@
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, BufWriter, ErrorKind, Read, Write};
+use std::iter::{zip, Peekable};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::runner::Options;
+
+static NEXT_SPILL_FILE_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// The maximum number of runs merged at once, which bounds the number of open files.
+const MAX_MERGE_WIDTH: usize = 64;
+const DEFAULT_CHUNK_SIZE: usize = 1 << 20;
+
+/// Solves both parts from stdin with `--chunk-size N` numbers per column in memory, for the columns given by
+/// `--columns A,B` (counted from 1, defaulting to the first two).
+pub fn main(options: &Options) -> Result<(), String> {
+    let chunk_size = match options.get("chunk-size") {
+        None => DEFAULT_CHUNK_SIZE,
+        Some(chunk_size) => chunk_size.parse::<usize>().ok()
+            .filter(|chunk_size| *chunk_size > 0)
+            .ok_or(format!("invalid chunk size: {}", chunk_size))?,
+    };
+
+    let (column, other_column) = match options.get("columns") {
+        None => (0, 1),
+        Some(columns) => columns.split_once(',')
+            .and_then(|(column, other_column)| Some((column.parse::<usize>().ok()?, other_column.parse::<usize>().ok()?)))
+            .filter(|(column, other_column)| *column > 0 && *other_column > 0)
+            .map(|(column, other_column)| (column - 1, other_column - 1))
+            .ok_or(format!("invalid columns: {}", columns))?,
+    };
+
+    let (distance, similarity_score) = solve(io::stdin().lock(), column, other_column, chunk_size)
+        .map_err(|error| error.to_string())?;
+
+    println!("DAY 01");
+    println!("  PART 1: {}", distance);
+    println!("  PART 2: {}", similarity_score);
+
+    Ok(())
+}
+
+/// Solves both parts for the given column pair while keeping at most `chunk_size` numbers per column in memory.
+/// Each column is sorted externally: sorted chunks are spilled to temporary files and merged afterwards.
+/// Part 1 zips the two merged columns, part 2 joins them on equal numbers, so neither needs a map of all numbers.
+pub fn solve(reader: impl Read, column: usize, other_column: usize, chunk_size: usize) -> io::Result<(i64, i64)> {
+    let mut chunks = (vec![], vec![]);
+    let mut runs = (vec![], vec![]);
+    let mut column_count = None;
+
+    for (line_index, line) in BufReader::new(reader).lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let numbers = line.split_whitespace()
+            .map(|number| number.parse::<i32>()
+                .map_err(|_| invalid_data(format!("line {}: invalid number '{}'", line_index + 1, number))))
+            .collect::<io::Result<Vec<i32>>>()?;
+
+        // like `Lists`, the first row sets the number of columns and every other row must match it
+        let expected_column_count = *column_count.get_or_insert(numbers.len());
+        let minimum_column_count = column.max(other_column).max(1) + 1;
+        if expected_column_count < minimum_column_count {
+            return Err(invalid_data(format!("line {}: expected at least {} columns, found {}", line_index + 1, minimum_column_count, numbers.len())));
+        }
+        if numbers.len() != expected_column_count {
+            return Err(invalid_data(format!("line {}: expected {} columns, found {}", line_index + 1, expected_column_count, numbers.len())));
+        }
+
+        chunks.0.push(numbers[column]);
+        chunks.1.push(numbers[other_column]);
+
+        if chunks.0.len() >= chunk_size {
+            runs.0.push(SpillFile::sorted(&mut chunks.0)?);
+            runs.1.push(SpillFile::sorted(&mut chunks.1)?);
+        }
+    }
+
+    if !chunks.0.is_empty() {
+        runs.0.push(SpillFile::sorted(&mut chunks.0)?);
+        runs.1.push(SpillFile::sorted(&mut chunks.1)?);
+    }
+
+    let runs = (reduce(runs.0)?, reduce(runs.1)?);
+
+    let distance = zip(merge(&runs.0)?, merge(&runs.1)?)
+        .map(|(a, b)| Ok((a? as i64 - b? as i64).abs()))
+        .sum::<io::Result<i64>>()?;
+
+    let similarity_score = calculate_similarity_score(merge(&runs.0)?, merge(&runs.1)?)?;
+
+    Ok((distance, similarity_score))
+}
+
+/// Walks both sorted columns in lockstep and adds `number * count * other_count` for every number found in both.
+fn calculate_similarity_score(numbers: impl Iterator<Item=io::Result<i32>>, other_numbers: impl Iterator<Item=io::Result<i32>>) -> io::Result<i64> {
+    let mut numbers = numbers.peekable();
+    let mut other_numbers = other_numbers.peekable();
+    let mut similarity_score = 0;
+
+    while let (Some(number), Some(other_number)) = (peek(&mut numbers)?, peek(&mut other_numbers)?) {
+        if number < other_number {
+            count_and_skip(&mut numbers, number)?;
+        } else if number > other_number {
+            count_and_skip(&mut other_numbers, other_number)?;
+        } else {
+            let count = count_and_skip(&mut numbers, number)?;
+            let other_count = count_and_skip(&mut other_numbers, number)?;
+            similarity_score += number as i64 * count * other_count;
+        }
+    }
+
+    Ok(similarity_score)
+}
+
+fn peek(numbers: &mut Peekable<impl Iterator<Item=io::Result<i32>>>) -> io::Result<Option<i32>> {
+    match numbers.peek() {
+        Some(Ok(number)) => Ok(Some(*number)),
+        Some(Err(_)) => numbers.next().unwrap().map(Some),
+        None => Ok(None),
+    }
+}
+
+fn count_and_skip(numbers: &mut Peekable<impl Iterator<Item=io::Result<i32>>>, number: i32) -> io::Result<i64> {
+    let mut count = 0;
+
+    while peek(numbers)? == Some(number) {
+        numbers.next();
+        count += 1;
+    }
+
+    Ok(count)
+}
+
+/// Merges the runs in passes of at most `MAX_MERGE_WIDTH` runs each, until they can be merged at once.
+fn reduce(mut runs: Vec<SpillFile>) -> io::Result<Vec<SpillFile>> {
+    while runs.len() > MAX_MERGE_WIDTH {
+        runs = runs.chunks(MAX_MERGE_WIDTH)
+            .map(SpillFile::merged)
+            .collect::<io::Result<Vec<SpillFile>>>()?;
+    }
+
+    Ok(runs)
+}
+
+/// Merges the sorted runs into one sorted stream, holding one number per run in memory.
+fn merge(runs: &[SpillFile]) -> io::Result<impl Iterator<Item=io::Result<i32>>> {
+    let mut readers = runs.iter()
+        .map(|run| File::open(&run.path).map(BufReader::new))
+        .collect::<io::Result<Vec<BufReader<File>>>>()?;
+
+    let mut heap = BinaryHeap::new();
+    for (index, reader) in readers.iter_mut().enumerate() {
+        if let Some(number) = read_number(reader)? {
+            heap.push(Reverse((number, index)));
+        }
+    }
+
+    Ok(std::iter::from_fn(move || {
+        let Reverse((number, index)) = heap.pop()?;
+
+        match read_number(&mut readers[index]) {
+            Ok(Some(next_number)) => heap.push(Reverse((next_number, index))),
+            Ok(None) => {}
+            Err(error) => return Some(Err(error)),
+        }
+
+        Some(Ok(number))
+    }))
+}
+
+fn read_number(reader: &mut impl Read) -> io::Result<Option<i32>> {
+    let mut bytes = [0; 4];
+
+    match reader.read_exact(&mut bytes) {
+        Ok(()) => Ok(Some(i32::from_le_bytes(bytes))),
+        Err(error) if error.kind() == ErrorKind::UnexpectedEof => Ok(None),
+        Err(error) => Err(error),
+    }
+}
+
+fn invalid_data(message: String) -> io::Error {
+    io::Error::new(ErrorKind::InvalidData, message)
+}
+
+/// Temporary file holding a sorted run of numbers, which is deleted once dropped.
+struct SpillFile {
+    path: PathBuf,
+}
+
+impl SpillFile {
+    /// Sorts the chunk, writes it to a new spill file and clears it.
+    fn sorted(chunk: &mut Vec<i32>) -> io::Result<SpillFile> {
+        chunk.sort();
+
+        let spill_file = SpillFile::write(chunk.iter().map(|number| Ok(*number)))?;
+
+        chunk.clear();
+        Ok(spill_file)
+    }
+
+    /// Writes the merged runs to a new spill file.
+    fn merged(runs: &[SpillFile]) -> io::Result<SpillFile> {
+        SpillFile::write(merge(runs)?)
+    }
+
+    fn write(numbers: impl Iterator<Item=io::Result<i32>>) -> io::Result<SpillFile> {
+        let id = NEXT_SPILL_FILE_ID.fetch_add(1, Ordering::Relaxed);
+        let path = std::env::temp_dir().join(format!("day01-{}-{}.run", std::process::id(), id));
+        let spill_file = SpillFile { path };
+
+        let mut writer = BufWriter::new(File::create(&spill_file.path)?);
+        for number in numbers {
+            writer.write_all(&number?.to_le_bytes())?;
+        }
+        writer.flush()?;
+
+        Ok(spill_file)
+    }
+}
+
+impl Drop for SpillFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::super::Lists;
+    use super::*;
+
+    fn assert_same_answers(input: &str, column: usize, other_column: usize) {
+        let lists = Lists::from_str(input).unwrap();
+        let expected = (
+            lists.calculate_distance(column, other_column) as i64,
+            lists.calculate_similarity_score(column, other_column) as i64,
+        );
+
+        for chunk_size in [1, 2, 3, 7, 100, 10_000] {
+            assert_eq!(solve(input.as_bytes(), column, other_column, chunk_size).unwrap(), expected, "chunk size {}", chunk_size);
+        }
+    }
+
+    #[test]
+    fn matches_lists_on_puzzle_input() {
+        // with a chunk size of 1, there are more runs than can be merged at once
+        assert_same_answers(include_str!("input.txt"), 0, 1);
+    }
+
+    #[test]
+    fn matches_lists_on_other_columns() {
+        let input = "3 4 1\n4 3 2\n2 5 3\n1 3 3\n3 9 3\n3 3 4\n";
+
+        assert_same_answers(input, 0, 2);
+        assert_same_answers(input, 2, 1);
+    }
+
+    #[test]
+    fn reports_invalid_numbers() {
+        let error = solve("1 2\n3 x\n".as_bytes(), 0, 1, 2).unwrap_err();
+
+        assert_eq!(error.to_string(), "line 2: invalid number 'x'");
+    }
+
+    #[test]
+    fn requires_the_column_count_of_the_first_row() {
+        let error = solve("1 2 3\n4 5\n".as_bytes(), 0, 1, 2).unwrap_err();
+        assert_eq!(error.to_string(), "line 2: expected 3 columns, found 2");
+
+        let error = solve("1 2\n4 5 6\n".as_bytes(), 0, 1, 2).unwrap_err();
+        assert_eq!(error.to_string(), "line 2: expected 2 columns, found 3");
+    }
+
+    #[test]
+    fn requires_the_selected_columns_on_the_first_row() {
+        let error = solve("1\n2\n".as_bytes(), 0, 0, 2).unwrap_err();
+        assert_eq!(error.to_string(), "line 1: expected at least 2 columns, found 1");
+
+        let error = solve("1 2\n3 4\n".as_bytes(), 0, 2, 2).unwrap_err();
+        assert_eq!(error.to_string(), "line 1: expected at least 3 columns, found 2");
+    }
+}
@@ -1,44 +1,163 @@
+use std::fmt::{Display, Formatter};
+
+use crate::runner::{self, Run};
+
+const SAFE_STEPS: StepBounds = StepBounds { min: 1, max: 3 };
+
 #[allow(dead_code)]
 pub fn main() {
     let input = include_str!("input.txt");
 
-    println!("PART 1: {}", part1(input));
-    println!("PART 2: {}", part2(input));
+    runner::run(input, solve).print();
+}
+
+pub fn solve(input: &str, run: &mut Run) {
+    run.part(|| part1(input));
+    run.part(|| part2(input));
 }
 
 fn part1(input: &str) -> usize {
+    count_safe_reports(input, 0, &SAFE_STEPS)
+}
+
+fn part2(input: &str) -> usize {
+    count_safe_reports(input, 1, &SAFE_STEPS)
+}
+
+fn count_safe_reports(input: &str, max_removals: usize, steps: &StepBounds) -> usize {
     input
         .lines()
-        .map(|line| line
-            .split_whitespace()
-            .map(|level| level.parse::<i32>().unwrap_or_default())
-            .map_windows(|[a, b]| b - a))
-        .map(is_report_safe)
-        .filter(|is_safe| *is_safe)
+        .map(parse_levels)
+        .filter(|levels| is_report_safe(levels, max_removals, steps))
         .count()
 }
 
-fn part2(input: &str) -> usize {
+/// Prints for every report whether it is increasing or decreasing, the first step outside the safe step bounds
+/// and which single level's removal makes it safe.
+pub fn explain(input: &str) {
     input
         .lines()
-        .map(|line| {
-            let levels = line.split_whitespace()
-                .map(|level| level.parse::<i32>().unwrap_or_default());
-
-            (0..levels.clone().count())
-                .map(|i| levels.clone().enumerate()
-                    .filter_map(|(index, level)| if index == i { None } else { Some(level) })
-                    .map_windows(|[a, b]| b - a)
-                    .collect::<Vec<i32>>())
-                .any(|report| is_report_safe(report.into_iter()))
-        })
-        .filter(|is_safe| *is_safe)
-        .count()
+        .map(parse_levels)
+        .enumerate()
+        .for_each(|(index, levels)| println!("REPORT {}: {}", index + 1, explain_report(&levels, &SAFE_STEPS)));
+}
+
+fn explain_report(levels: &[i32], steps: &StepBounds) -> Explanation {
+    let differences = levels.iter().map_windows(|[a, b]| *b - *a).collect::<Vec<i32>>();
+
+    let increasing_count = differences.iter().filter(|difference| **difference > 0).count();
+    let decreasing_count = differences.iter().filter(|difference| **difference < 0).count();
+    let direction = if increasing_count >= decreasing_count { Direction::Increasing } else { Direction::Decreasing };
+
+    let first_offending_difference = differences.iter()
+        .position(|difference| !steps.contains(direction.sign() * difference))
+        .map(|index| (index, differences[index]));
+
+    let safe_after_removing = match first_offending_difference {
+        None => None,
+        Some(_) => (0..levels.len()).find(|removed_index| {
+            let remaining_levels = levels.iter()
+                .enumerate()
+                .filter(|(index, _)| index != removed_index)
+                .map(|(_, level)| *level)
+                .collect::<Vec<i32>>();
+
+            is_report_safe(&remaining_levels, 0, steps)
+        }),
+    };
+
+    Explanation { direction, first_offending_difference, safe_after_removing }
+}
+
+fn parse_levels(line: &str) -> Vec<i32> {
+    line.split_whitespace()
+        .map(|level| level.parse::<i32>().unwrap_or_default())
+        .collect()
 }
 
-fn is_report_safe(report: impl Iterator<Item=i32> + Clone) -> bool {
-    let is_increasing = report.clone().all(|difference| difference >= 1 && difference <= 3);
-    let is_decreasing = report.clone().all(|difference| difference <= -1 && difference >= -3);
+/// A report is safe if, after removing at most `max_removals` levels, it is either increasing or decreasing
+/// with every step between adjacent levels within the step bounds.
+fn is_report_safe(levels: &[i32], max_removals: usize, steps: &StepBounds) -> bool {
+    let is_increasing = count_required_removals(levels, max_removals, |a, b| steps.contains(b - a)) <= max_removals;
+    let is_decreasing = count_required_removals(levels, max_removals, |a, b| steps.contains(a - b)) <= max_removals;
 
     is_increasing || is_decreasing
 }
+
+/// Calculates the minimum number of levels to remove, so that every step between the remaining levels is allowed.
+/// Only removals of up to `max_removals` adjacent levels are considered, which makes this O(n * max_removals);
+/// if more removals are required, the result is larger than `max_removals`.
+fn count_required_removals(levels: &[i32], max_removals: usize, is_allowed_step: impl Fn(i32, i32) -> bool) -> usize {
+    // removals_up_to[i] is the minimum number of removed levels before index i, if the level at i is kept
+    let mut removals_up_to: Vec<usize> = Vec::with_capacity(levels.len());
+
+    for i in 0..levels.len() {
+        let removals = (i.saturating_sub(max_removals + 1)..i)
+            .filter(|j| is_allowed_step(levels[*j], levels[i]))
+            .map(|j| removals_up_to[j] + (i - j - 1))
+            .min()
+            .unwrap_or(i)
+            .min(i);
+
+        removals_up_to.push(removals);
+    }
+
+    removals_up_to.iter()
+        .enumerate()
+        .map(|(i, removals)| removals + (levels.len() - 1 - i))
+        .min()
+        .unwrap_or(0)
+}
+
+struct StepBounds {
+    min: i32,
+    max: i32,
+}
+
+impl StepBounds {
+    fn contains(&self, step: i32) -> bool {
+        (self.min..=self.max).contains(&step)
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Direction {
+    Increasing,
+    Decreasing,
+}
+
+impl Direction {
+    fn sign(&self) -> i32 {
+        match self {
+            Direction::Increasing => 1,
+            Direction::Decreasing => -1,
+        }
+    }
+}
+
+struct Explanation {
+    direction: Direction,
+    /// Index into the differences between adjacent levels, i.e. the step from level `index` to `index + 1`.
+    first_offending_difference: Option<(usize, i32)>,
+    safe_after_removing: Option<usize>,
+}
+
+impl Display for Explanation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let direction = match self.direction {
+            Direction::Increasing => "increasing",
+            Direction::Decreasing => "decreasing",
+        };
+
+        let Some((index, difference)) = self.first_offending_difference else {
+            return write!(f, "{}, safe", direction);
+        };
+
+        write!(f, "{}, unsafe: difference {} at index {} (levels[{}] -> levels[{}])", direction, difference, index, index, index + 1)?;
+
+        match self.safe_after_removing {
+            Some(removed_index) => write!(f, "; safe after removing levels[{}]", removed_index),
+            None => write!(f, "; no single removal makes it safe"),
+        }
+    }
+}
@@ -1,68 +1,223 @@
-use regex::Regex;
+use crate::runner::{self, Run};
+
+/// The instructions of the puzzle. Further instructions are declared the same way, e.g. an `add(a,b)` as
+/// `Instruction { name: "add", arity: 2, max_digits: 3, effect: Effect::Accumulate(|arguments| arguments[0] + arguments[1]) }`.
+const PUZZLE_INSTRUCTIONS: [Instruction; 3] = [
+    Instruction { name: "mul", arity: 2, max_digits: 3, effect: Effect::Accumulate(|arguments| arguments[0] * arguments[1]) },
+    Instruction { name: "do", arity: 0, max_digits: 0, effect: Effect::Enable },
+    Instruction { name: "don't", arity: 0, max_digits: 0, effect: Effect::Disable },
+];
 
 #[allow(dead_code)]
 pub fn main() {
     let input = include_str!("input.txt");
 
-    println!("PART 1: {}", part1(input));
-    println!("PART 2: {}", part2(input));
+    runner::run(input, solve).print();
 }
 
-fn part1(input: &str) -> u32 {
-    let regex = Regex::new(r"mul\(([0-9]{1,3}),([0-9]{1,3})\)").unwrap();
+pub fn solve(input: &str, run: &mut Run) {
+    let tokens = run.parse(|| tokenize(input, &PUZZLE_INSTRUCTIONS));
 
-    regex.captures_iter(input)
-        .map(|captures| {
-            let (_, [arg1, arg2]) = captures.extract();
+    run.part(|| part1(&tokens));
+    run.part(|| part2(&tokens));
+}
 
-            let a = arg1.parse::<u32>().unwrap_or_default();
-            let b = arg2.parse::<u32>().unwrap_or_default();
+fn part1(tokens: &[Token]) -> i64 {
+    Interpreter { instructions: &PUZZLE_INSTRUCTIONS, respects_conditionals: false }.execute(tokens)
+}
 
-            a * b
-        })
-        .sum()
+fn part2(tokens: &[Token]) -> i64 {
+    Interpreter { instructions: &PUZZLE_INSTRUCTIONS, respects_conditionals: true }.execute(tokens)
 }
 
-fn part2(input: &str) -> u32 {
-    const DO_PATTERN: &str = "do()";
-    const DONT_PATTERN: &str = "don't()";
-    let mul_regex = Regex::new(r"^mul\(([0-9]{1,3}),([0-9]{1,3})\)").unwrap();
+/// Prints the token stream with the byte positions of every token.
+pub fn explain(input: &str) {
+    tokenize(input, &PUZZLE_INSTRUCTIONS).iter().for_each(|token| match &token.kind {
+        TokenKind::Garbage => println!("{}..{}: garbage {:?}", token.start, token.end, &input[token.start..token.end]),
+        TokenKind::Instruction { index, arguments } => println!(
+            "{}..{}: {}({})",
+            token.start, token.end, PUZZLE_INSTRUCTIONS[*index].name,
+            arguments.iter().map(|argument| argument.to_string()).collect::<Vec<String>>().join(",")
+        ),
+    });
+}
 
-    let mut is_mul_enabled = true;
-    let mut sum: u32 = 0;
-    let mut i: usize = 0;
+/// An instruction of the form `name(argument,...)`, where every argument is a number of 1 to `max_digits` digits.
+struct Instruction {
+    name: &'static str,
+    arity: usize,
+    max_digits: usize,
+    effect: Effect,
+}
 
-    while i < input.len() {
-        let substring = &input[i..];
+enum Effect {
+    /// Adds the result to the sum, if instructions are enabled.
+    Accumulate(fn(&[i64]) -> i64),
+    Enable,
+    Disable,
+}
 
-        if substring.starts_with(DO_PATTERN) {
-            i += DO_PATTERN.len();
-            is_mul_enabled = true;
-            continue;
-        }
+enum TokenKind {
+    /// An instruction with its index into the instruction set.
+    Instruction { index: usize, arguments: Vec<i64> },
+    Garbage,
+}
 
-        if substring.starts_with(DONT_PATTERN) {
-            i += DONT_PATTERN.len();
-            is_mul_enabled = false;
-            continue;
-        }
+/// A token spanning the bytes `start..end` of the input.
+struct Token {
+    kind: TokenKind,
+    start: usize,
+    end: usize,
+}
 
-        let Some(captures) = mul_regex.captures(substring) else {
+/// Splits the corrupted memory into instructions and the garbage between them in a single pass.
+/// At every position at most the length of each instruction is looked at, so this runs in linear time.
+fn tokenize(input: &str, instructions: &[Instruction]) -> Vec<Token> {
+    let bytes = input.as_bytes();
+    let mut tokens = vec![];
+    let mut garbage_start = 0;
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let Some((kind, length)) = match_instruction(&bytes[i..], instructions) else {
             i += 1;
             continue;
         };
 
-        let (capture, [arg1, arg2]) = captures.extract();
-        i += capture.len();
+        if garbage_start < i {
+            tokens.push(Token { kind: TokenKind::Garbage, start: garbage_start, end: i });
+        }
 
-        if !is_mul_enabled {
-            continue;
+        tokens.push(Token { kind, start: i, end: i + length });
+        i += length;
+        garbage_start = i;
+    }
+
+    if garbage_start < bytes.len() {
+        tokens.push(Token { kind: TokenKind::Garbage, start: garbage_start, end: bytes.len() });
+    }
+
+    tokens
+}
+
+/// Returns the first instruction of the set found at the start of `bytes` and its length in bytes.
+fn match_instruction(bytes: &[u8], instructions: &[Instruction]) -> Option<(TokenKind, usize)> {
+    instructions.iter()
+        .enumerate()
+        .find_map(|(index, instruction)| {
+            let (arguments, length) = instruction.match_at(bytes)?;
+            Some((TokenKind::Instruction { index, arguments }, length))
+        })
+}
+
+impl Instruction {
+    fn match_at(&self, bytes: &[u8]) -> Option<(Vec<i64>, usize)> {
+        let mut rest = bytes.strip_prefix(self.name.as_bytes())?.strip_prefix(b"(")?;
+        let mut arguments = Vec::with_capacity(self.arity);
+
+        for i in 0..self.arity {
+            if i > 0 {
+                rest = rest.strip_prefix(b",")?;
+            }
+
+            let (argument, length) = match_number(rest, self.max_digits)?;
+            arguments.push(argument);
+            rest = &rest[length..];
         }
 
-        let a = arg1.parse::<u32>().unwrap_or_default();
-        let b = arg2.parse::<u32>().unwrap_or_default();
-        sum += a * b;
+        rest = rest.strip_prefix(b")")?;
+
+        Some((arguments, bytes.len() - rest.len()))
     }
+}
+
+/// Matches a number with 1 to `max_digits` digits and returns it along with its length.
+/// Numbers that do not fit into an `i64` do not match.
+fn match_number(bytes: &[u8], max_digits: usize) -> Option<(i64, usize)> {
+    let length = bytes.iter().take(max_digits).take_while(|byte| byte.is_ascii_digit()).count();
+    if length == 0 {
+        return None;
+    }
+
+    let number = bytes[..length].iter()
+        .try_fold(0i64, |number, digit| number.checked_mul(10)?.checked_add((digit - b'0') as i64))?;
+    Some((number, length))
+}
+
+struct Interpreter<'a> {
+    instructions: &'a [Instruction],
+    respects_conditionals: bool,
+}
+
+impl Interpreter<'_> {
+    fn execute(&self, tokens: &[Token]) -> i64 {
+        let mut is_enabled = true;
+        let mut sum = 0;
+
+        for token in tokens {
+            let TokenKind::Instruction { index, arguments } = &token.kind else { continue; };
+
+            match self.instructions[*index].effect {
+                Effect::Accumulate(calculate) if is_enabled => sum += calculate(arguments),
+                Effect::Accumulate(_) => {}
+                Effect::Enable => is_enabled = true,
+                Effect::Disable => is_enabled = !self.respects_conditionals,
+            }
+        }
 
-    sum
+        sum
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn handles_multi_byte_characters_next_to_instructions() {
+        let input = "émul(2,3)ü don't()ßmul(4,5)✓do()🎄mul(1,1)é";
+        let tokens = tokenize(input, &PUZZLE_INSTRUCTIONS);
+
+        assert_eq!(part1(&tokens), 27);
+        assert_eq!(part2(&tokens), 7);
+
+        let garbage = tokens.iter()
+            .filter(|token| matches!(token.kind, TokenKind::Garbage))
+            .inspect(|token| assert!(input.is_char_boundary(token.start) && input.is_char_boundary(token.end)))
+            .map(|token| &input[token.start..token.end])
+            .collect::<Vec<&str>>();
+
+        assert_eq!(garbage, ["é", "ü ", "ß", "✓", "🎄", "é"]);
+    }
+
+    #[test]
+    fn executes_further_instructions() {
+        let instructions = [
+            Instruction { name: "add", arity: 2, max_digits: 3, effect: Effect::Accumulate(|arguments| arguments[0] + arguments[1]) },
+            Instruction { name: "neg", arity: 1, max_digits: 5, effect: Effect::Accumulate(|arguments| -arguments[0]) },
+        ];
+        let input = "add(12,345)neg(12345)add(1,2,3)neg(123456)mul(2,3)";
+        let tokens = tokenize(input, &instructions);
+
+        let kinds = tokens.iter()
+            .map(|token| match &token.kind {
+                TokenKind::Instruction { index, arguments } => (Some(*index), arguments.clone(), &input[token.start..token.end]),
+                TokenKind::Garbage => (None, vec![], &input[token.start..token.end]),
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(kinds, [
+            (Some(0), vec![12, 345], "add(12,345)"),
+            (Some(1), vec![12345], "neg(12345)"),
+            (None, vec![], "add(1,2,3)neg(123456)mul(2,3)"),
+        ]);
+        assert_eq!(Interpreter { instructions: &instructions, respects_conditionals: true }.execute(&tokens), 357 - 12345);
+    }
+
+    #[test]
+    fn rejects_numbers_that_overflow() {
+        assert_eq!(match_number(b"9223372036854775807)", 19), Some((i64::MAX, 19)));
+        assert_eq!(match_number(b"9223372036854775808)", 19), None);
+        assert_eq!(match_number(b"99999999999999999999)", 20), None);
+    }
 }
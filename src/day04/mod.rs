@@ -1,113 +1,127 @@
 use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
 use std::str::FromStr;
 
+use crate::runner::{self, Run};
+
+const X_MAS: &str = "M.S\n.A.\nM.S";
+const DIRECTIONS: [(i32, i32); 8] = [(-1, -1), (-1, 0), (-1, 1), (0, -1), (0, 1), (1, -1), (1, 0), (1, 1)];
+
 #[allow(dead_code)]
 pub fn main() {
     let input = include_str!("input.txt");
-    let grid = Grid::from_str(input).unwrap();
 
-    println!("PART 1: {}", part1(&grid));
-    println!("PART 2: {}", part2(&grid));
+    runner::run(input, solve).print();
+}
+
+pub fn solve(input: &str, run: &mut Run) {
+    let grid = run.parse(|| Grid::from_str(input).unwrap());
+
+    run.part(|| part1(&grid));
+    run.part(|| part2(&grid));
 }
 
 fn part1(grid: &Grid) -> usize {
-    (0..grid.height)
-        .flat_map(move |row_index| (0..grid.width).map(move |column_index| (row_index, column_index)))
-        .map(|(row_index, column_index)| grid.calculate_xmas_count_at(row_index, column_index))
-        .sum()
+    grid.find_word("XMAS").len()
 }
 
 fn part2(grid: &Grid) -> usize {
-    (0..grid.height)
-        .flat_map(move |row_index| (0..grid.width).map(move |column_index| (row_index, column_index)))
-        .filter(|(row_index, column_index)| grid.is_x_mas_at(*row_index, *column_index))
-        .count()
+    grid.find_template(&Template::from_str(X_MAS).unwrap(), true).len()
+}
+
+/// Prints the positions of all matches of both parts.
+pub fn explain(input: &str) {
+    let grid = Grid::from_str(input).unwrap();
+
+    grid.find_word("XMAS").iter().for_each(|word_match| println!(
+        "XMAS at ({}, {}) in direction ({}, {})",
+        word_match.row_index, word_match.column_index, word_match.direction.0, word_match.direction.1
+    ));
+
+    grid.find_template(&Template::from_str(X_MAS).unwrap(), true).iter().for_each(|template_match| println!(
+        "X-MAS at ({}, {}) as {}",
+        template_match.row_index, template_match.column_index, template_match.template
+    ));
 }
 
 struct Grid {
-    width: usize,
-    height: usize,
-    cells: HashMap<(usize, usize), char>,
+    width: i32,
+    height: i32,
+    cells: HashMap<(i32, i32), char>,
+}
+
+/// A word found in the grid, starting at `(row_index, column_index)` and continuing in `direction` (row and column step).
+struct WordMatch {
+    row_index: i32,
+    column_index: i32,
+    direction: (i32, i32),
+}
+
+/// A template found in the grid, with `(row_index, column_index)` being the top left corner of the (transformed) template.
+struct TemplateMatch {
+    row_index: i32,
+    column_index: i32,
+    template: Template,
 }
 
 impl Grid {
-    fn calculate_xmas_count_at(&self, row_index: usize, column_index: usize) -> usize {
-        [
-            self.is_horizontal_xmas_at(row_index, column_index),
-            self.is_vertical_xmas_at(row_index, column_index),
-            self.is_right_diagonal_xmas_at(row_index, column_index),
-            self.is_left_diagonal_xmas_at(row_index, column_index)
-        ]
-            .iter()
-            .filter(|is_xmas| **is_xmas)
-            .count()
-    }
-
-    fn is_horizontal_xmas_at(&self, row_index: usize, column_index: usize) -> bool {
-        self.is_xmas([
-            (row_index, column_index),
-            (row_index, column_index + 1),
-            (row_index, column_index + 2),
-            (row_index, column_index + 3)
-        ])
-    }
-
-    fn is_vertical_xmas_at(&self, row_index: usize, column_index: usize) -> bool {
-        self.is_xmas([
-            (row_index, column_index),
-            (row_index + 1, column_index),
-            (row_index + 2, column_index),
-            (row_index + 3, column_index)
-        ])
-    }
-
-    fn is_right_diagonal_xmas_at(&self, row_index: usize, column_index: usize) -> bool {
-        self.is_xmas([
-            (row_index, column_index),
-            (row_index + 1, column_index + 1),
-            (row_index + 2, column_index + 2),
-            (row_index + 3, column_index + 3)
-        ])
-    }
-
-    fn is_left_diagonal_xmas_at(&self, row_index: usize, column_index: usize) -> bool {
-        self.is_xmas([
-            (row_index, column_index),
-            (row_index - 1, column_index + 1),
-            (row_index - 2, column_index + 2),
-            (row_index - 3, column_index + 3)
-        ])
-    }
-
-    fn is_xmas(&self, indices: [(usize, usize); 4]) -> bool {
-        "XMAS".char_indices().all(|(i, char)| self.get_char_at(indices[i].0, indices[i].1) == &char) ||
-            "SAMX".char_indices().all(|(i, char)| self.get_char_at(indices[i].0, indices[i].1) == &char)
-    }
-
-    fn get_char_at(&self, row_index: usize, column_index: usize) -> &char {
-        self.cells.get(&(row_index, column_index)).unwrap_or(&'.')
-    }
-
-    fn is_x_mas_at(&self, row_index: usize, column_index: usize) -> bool {
-        (
-            self.get_char_at(row_index, column_index) == &'A'
-        ) && (
-            (
-                self.get_char_at(row_index - 1, column_index - 1) == &'M' &&
-                    self.get_char_at(row_index + 1, column_index + 1) == &'S'
-            ) || (
-                self.get_char_at(row_index - 1, column_index - 1) == &'S' &&
-                    self.get_char_at(row_index + 1, column_index + 1) == &'M'
-            )
-        ) && (
-            (
-                self.get_char_at(row_index - 1, column_index + 1) == &'M' &&
-                    self.get_char_at(row_index + 1, column_index - 1) == &'S'
-            ) || (
-                self.get_char_at(row_index - 1, column_index + 1) == &'S' &&
-                    self.get_char_at(row_index + 1, column_index - 1) == &'M'
-            )
-        )
+    /// Finds all occurrences of the word in any of the 8 directions.
+    fn find_word(&self, word: &str) -> Vec<WordMatch> {
+        let chars = word.chars().collect::<Vec<char>>();
+
+        self.positions()
+            .flat_map(|(row_index, column_index)| DIRECTIONS.iter()
+                .map(move |direction| WordMatch { row_index, column_index, direction: *direction }))
+            .filter(|word_match| chars.iter().enumerate().all(|(i, char)| {
+                let row_index = word_match.row_index + word_match.direction.0 * i as i32;
+                let column_index = word_match.column_index + word_match.direction.1 * i as i32;
+
+                self.get_char_at(row_index, column_index) == Some(*char)
+            }))
+            .collect()
+    }
+
+    /// Finds all occurrences of the template. If `with_transformations` is set, the rotations and reflections of the
+    /// template are searched as well; transformations resulting in the same template are only searched once.
+    fn find_template(&self, template: &Template, with_transformations: bool) -> Vec<TemplateMatch> {
+        let templates = if with_transformations { template.transformations() } else { vec![template.clone()] };
+
+        templates.iter()
+            .flat_map(|template| self.positions()
+                .filter(|(row_index, column_index)| self.is_template_at(template, *row_index, *column_index))
+                .map(|(row_index, column_index)| TemplateMatch { row_index, column_index, template: template.clone() }))
+            .collect()
+    }
+
+    /// The whole template has to be on the grid, including its wildcards.
+    fn is_template_at(&self, template: &Template, row_index: i32, column_index: i32) -> bool {
+        if !self.is_on_grid(row_index + template.height() as i32 - 1, column_index + template.width() as i32 - 1) {
+            return false;
+        }
+
+        template.cells.iter().enumerate().all(|(template_row_index, row)| row.iter().enumerate()
+            .all(|(template_column_index, cell)| match cell {
+                None => true,
+                Some(char) => self.get_char_at(row_index + template_row_index as i32, column_index + template_column_index as i32) == Some(*char),
+            }))
+    }
+
+    fn positions(&self) -> impl Iterator<Item=(i32, i32)> + '_ {
+        (0..self.height)
+            .flat_map(move |row_index| (0..self.width).map(move |column_index| (row_index, column_index)))
+    }
+
+    fn is_on_grid(&self, row_index: i32, column_index: i32) -> bool {
+        row_index >= 0 && row_index < self.height && column_index >= 0 && column_index < self.width
+    }
+
+    /// Returns `None` for positions outside of the grid.
+    fn get_char_at(&self, row_index: i32, column_index: i32) -> Option<char> {
+        if !self.is_on_grid(row_index, column_index) {
+            return None;
+        }
+
+        self.cells.get(&(row_index, column_index)).copied()
     }
 }
 
@@ -118,16 +132,118 @@ impl FromStr for Grid {
         let cells = s
             .lines()
             .enumerate()
-            .flat_map(move |(row_index, row)| row.char_indices()
-                .map(move |(column_index, char)| (row_index, column_index, char)))
+            .flat_map(move |(row_index, row)| row.chars()
+                .enumerate()
+                .map(move |(column_index, char)| (row_index as i32, column_index as i32, char)))
             .fold(HashMap::new(), |mut map, (row_index, column_index, char)| {
                 map.insert((row_index, column_index), char);
                 map
             });
 
-        let width = s.lines().next().unwrap_or_default().len();
-        let height = s.lines().count();
+        let width = s.lines().next().unwrap_or_default().chars().count() as i32;
+        let height = s.lines().count() as i32;
 
         Ok(Grid { width, height, cells })
     }
 }
+
+/// A rectangular pattern of chars, where `None` is a wildcard matching any char.
+#[derive(Clone, PartialEq)]
+struct Template {
+    cells: Vec<Vec<Option<char>>>,
+}
+
+impl Template {
+    fn width(&self) -> usize {
+        self.cells.first().map(|row| row.len()).unwrap_or_default()
+    }
+
+    fn height(&self) -> usize {
+        self.cells.len()
+    }
+
+    fn rotated(&self) -> Template {
+        Template {
+            cells: (0..self.width())
+                .map(|column_index| (0..self.height()).rev().map(|row_index| self.cells[row_index][column_index]).collect())
+                .collect(),
+        }
+    }
+
+    fn reflected(&self) -> Template {
+        Template {
+            cells: self.cells.iter().map(|row| row.iter().rev().copied().collect()).collect(),
+        }
+    }
+
+    /// Returns the distinct templates among all 4 rotations of the template and of its reflection.
+    fn transformations(&self) -> Vec<Template> {
+        let mut transformations: Vec<Template> = vec![];
+
+        for mut template in [self.clone(), self.reflected()] {
+            for _ in 0..4 {
+                if !transformations.contains(&template) {
+                    transformations.push(template.clone());
+                }
+
+                template = template.rotated();
+            }
+        }
+
+        transformations
+    }
+}
+
+impl FromStr for Template {
+    type Err = ();
+
+    /// Parses one row per line, with `.` as the wildcard.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let cells = s.lines()
+            .map(|row| row.chars().map(|char| if char == '.' { None } else { Some(char) }).collect::<Vec<Option<char>>>())
+            .collect::<Vec<Vec<Option<char>>>>();
+
+        if cells.iter().any(|row| row.len() != cells[0].len()) {
+            return Err(());
+        }
+
+        Ok(Template { cells })
+    }
+}
+
+impl Display for Template {
+    /// Writes the rows separated by `/`, with `.` as the wildcard.
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let rows = self.cells.iter()
+            .map(|row| row.iter().map(|cell| cell.unwrap_or('.')).collect::<String>())
+            .collect::<Vec<String>>();
+
+        write!(f, "{}", rows.join("/"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// XMAS along row 0, the last row, column 0 and the last column.
+    const XMAS_ON_EDGES: &str = "XMAS.\nM...S\nA...A\nS...M\n.SAMX";
+    /// X-MAS in every corner, with their arms on all four edges.
+    const X_MAS_ON_EDGES: &str = "M.S.M\n.A.A.\nM.S.M\n.A.A.\nM.S.M";
+
+    #[test]
+    fn finds_xmas_on_edges() {
+        let grid = Grid::from_str(XMAS_ON_EDGES).unwrap();
+
+        assert_eq!(part1(&grid), 4);
+        assert_eq!(part2(&grid), 0);
+    }
+
+    #[test]
+    fn finds_x_mas_on_edges() {
+        let grid = Grid::from_str(X_MAS_ON_EDGES).unwrap();
+
+        assert_eq!(part1(&grid), 0);
+        assert_eq!(part2(&grid), 4);
+    }
+}
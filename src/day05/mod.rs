@@ -1,36 +1,63 @@
+use std::collections::{BTreeSet, HashMap};
+use std::fmt::{Display, Formatter};
 use std::str::FromStr;
 
+use crate::runner::{self, Run};
+
 #[allow(dead_code)]
 pub fn main() {
     let input = include_str!("input.txt");
 
+    runner::run(input, solve).print();
+}
+
+pub fn solve(input: &str, run: &mut Run) {
     let [raw_rules, raw_manuals] = input.split("\n\n").collect::<Vec<&str>>()[..] else { return; };
 
+    let (rules, manuals) = run.parse(|| (
+        raw_rules.lines().map(|line| Rule::from_str(line).unwrap()).collect::<Vec<Rule>>(),
+        raw_manuals.lines().map(|line| Manual::from_str(line).unwrap()).collect::<Vec<Manual>>(),
+    ));
+
+    run.part(|| part1(&rules, &manuals));
+    run.part(|| part2(&rules, manuals).unwrap_or_else(|cycle| panic!("{}", cycle)));
+}
+
+/// Prints an analysis of the rules.
+pub fn explain(input: &str) {
+    let [raw_rules, _] = input.split("\n\n").collect::<Vec<&str>>()[..] else { return; };
     let rules = raw_rules.lines().map(|line| Rule::from_str(line).unwrap()).collect::<Vec<Rule>>();
-    let manuals = raw_manuals.lines().map(|line| Manual::from_str(line).unwrap()).collect::<Vec<Manual>>();
+    let rule_set = RuleSet::new(&rules);
 
-    println!("PART 1: {}", part1(&rules, &manuals));
-    println!("PART 2: {}", part2(&rules, manuals));
+    let format_rules = |rules: Vec<&Rule>| rules.iter()
+        .map(|rule| format!("{}|{}", rule.before, rule.after))
+        .collect::<Vec<String>>()
+        .join(", ");
+
+    let format_pairs = |pairs: Vec<(u32, u32)>| pairs.iter()
+        .map(|(page, other_page)| format!("{}/{}", page, other_page))
+        .collect::<Vec<String>>()
+        .join(", ");
+
+    println!("PAGES: {}", rule_set.pages.len());
+    println!("RULES: {}", rules.len());
+    println!("ORDERED PAIRS (TRANSITIVE CLOSURE): {}", rule_set.transitive_closure().len());
+    println!("REDUNDANT RULES: {}", format_rules(rule_set.redundant_rules()));
+    println!("CONFLICTING RULES: {}", format_rules(rule_set.conflicting_rules()));
+    println!("UNORDERED PAIRS: {}", format_pairs(rule_set.unordered_pairs()));
 }
 
-fn part1(rules: &Vec<Rule>, manuals: &Vec<Manual>) -> u32 {
+fn part1(rules: &[Rule], manuals: &[Manual]) -> u32 {
     manuals.iter()
         .filter(|manual| manual.satisfies_rules(rules.iter()))
         .map(|manual| manual.get_middle_page())
         .sum()
 }
 
-fn part2(rules: &Vec<Rule>, manuals: Vec<Manual>) -> u32 {
+fn part2(rules: &[Rule], manuals: Vec<Manual>) -> Result<u32, Cycle> {
     manuals.into_iter()
         .filter(|manual| !manual.satisfies_rules(rules.iter()))
-        .map(|mut manual| {
-            while !manual.satisfies_rules(rules.iter()) {
-                manual = manual.get_relevant_rules(rules.iter())
-                    .fold(manual.clone(), |new_manual, rule| new_manual.with_rule_satisfied(rule));
-            }
-
-            manual.get_middle_page()
-        })
+        .map(|manual| Ok(manual.sorted_by_rules(rules.iter())?.get_middle_page()))
         .sum()
 }
 
@@ -52,6 +79,131 @@ impl FromStr for Rule {
     }
 }
 
+/// Precedence graph of a set of rules with its transitive closure, which answers `must_precede` queries in O(1).
+struct RuleSet<'a> {
+    rules: &'a [Rule],
+    pages: Vec<u32>,
+    index_of_page: HashMap<u32, usize>,
+    successors: Vec<Vec<usize>>,
+    /// `reachable[a * pages.len() + b]` is set if page `a` has to come before page `b`, directly or transitively.
+    reachable: Vec<bool>,
+}
+
+impl<'a> RuleSet<'a> {
+    fn new(rules: &'a [Rule]) -> RuleSet<'a> {
+        let pages = rules.iter()
+            .flat_map(|rule| [rule.before, rule.after])
+            .collect::<BTreeSet<u32>>()
+            .into_iter()
+            .collect::<Vec<u32>>();
+
+        let index_of_page = pages.iter()
+            .enumerate()
+            .map(|(index, page)| (*page, index))
+            .collect::<HashMap<u32, usize>>();
+
+        let mut successors = vec![vec![]; pages.len()];
+        rules.iter().for_each(|rule| successors[index_of_page[&rule.before]].push(index_of_page[&rule.after]));
+
+        let mut reachable = vec![false; pages.len() * pages.len()];
+
+        for start in 0..pages.len() {
+            let mut stack = successors[start].clone();
+
+            while let Some(index) = stack.pop() {
+                if reachable[start * pages.len() + index] {
+                    continue;
+                }
+
+                reachable[start * pages.len() + index] = true;
+                stack.extend(&successors[index]);
+            }
+        }
+
+        RuleSet { rules, pages, index_of_page, successors, reachable }
+    }
+
+    fn must_precede(&self, page: u32, other_page: u32) -> bool {
+        match (self.index_of_page.get(&page), self.index_of_page.get(&other_page)) {
+            (Some(index), Some(other_index)) => self.is_reachable(*index, *other_index),
+            _ => false,
+        }
+    }
+
+    fn is_reachable(&self, index: usize, other_index: usize) -> bool {
+        self.reachable[index * self.pages.len() + other_index]
+    }
+
+    /// Returns all pairs of pages `(before, after)`, where `before` has to come before `after`.
+    fn transitive_closure(&self) -> Vec<(u32, u32)> {
+        (0..self.pages.len())
+            .flat_map(|index| (0..self.pages.len()).map(move |other_index| (index, other_index)))
+            .filter(|(index, other_index)| self.is_reachable(*index, *other_index))
+            .map(|(index, other_index)| (self.pages[index], self.pages[other_index]))
+            .collect()
+    }
+
+    /// Returns the rules that are implied by the other rules, including repetitions of an earlier rule. A rule is
+    /// implied if its `after` page is still reachable from its `before` page without it, which is checked on the rules
+    /// themselves, as paths in the transitive closure may lead through the rule when it is part of a cycle.
+    fn redundant_rules(&self) -> Vec<&'a Rule> {
+        self.rules.iter()
+            .enumerate()
+            .filter(|(rule_index, rule)| {
+                let (before, after) = (self.index_of_page[&rule.before], self.index_of_page[&rule.after]);
+
+                let is_repeated = self.rules[..*rule_index].iter()
+                    .any(|other_rule| other_rule.before == rule.before && other_rule.after == rule.after);
+
+                let is_implied = self.is_reachable_without_rule(before, after);
+
+                is_repeated || is_implied
+            })
+            .map(|(_, rule)| rule)
+            .collect()
+    }
+
+    fn is_reachable_without_rule(&self, before: usize, after: usize) -> bool {
+        let mut visited = vec![false; self.pages.len()];
+        let mut stack = vec![before];
+
+        while let Some(index) = stack.pop() {
+            for successor in &self.successors[index] {
+                if index == before && *successor == after {
+                    continue;
+                }
+
+                if *successor == after {
+                    return true;
+                }
+
+                if !visited[*successor] {
+                    visited[*successor] = true;
+                    stack.push(*successor);
+                }
+            }
+        }
+
+        false
+    }
+
+    /// Returns the rules that are part of a cycle, i.e. whose `after` page has to come before their `before` page as well.
+    fn conflicting_rules(&self) -> Vec<&'a Rule> {
+        self.rules.iter()
+            .filter(|rule| self.must_precede(rule.after, rule.before))
+            .collect()
+    }
+
+    /// Returns all pairs of distinct pages, for which the rules don't determine an order.
+    fn unordered_pairs(&self) -> Vec<(u32, u32)> {
+        (0..self.pages.len())
+            .flat_map(|index| ((index + 1)..self.pages.len()).map(move |other_index| (index, other_index)))
+            .filter(|(index, other_index)| !self.is_reachable(*index, *other_index) && !self.is_reachable(*other_index, *index))
+            .map(|(index, other_index)| (self.pages[index], self.pages[other_index]))
+            .collect()
+    }
+}
+
 #[derive(Clone)]
 struct Manual {
     pages: Vec<u32>,
@@ -75,19 +227,68 @@ impl Manual {
         *self.pages.get((self.pages.len() - 1) / 2).unwrap()
     }
 
-    fn with_rule_satisfied(&self, rule: &Rule) -> Manual {
-        if self.is_rule_satisfied(rule) {
-            return self.clone();
+    /// Orders the pages by a topological sort of the precedence graph of the relevant rules. Among pages
+    /// without any remaining predecessor, the one appearing first in the manual is taken first.
+    fn sorted_by_rules<'a>(&self, rules: impl Iterator<Item=&'a Rule>) -> Result<Manual, Cycle> {
+        let index_of_page = self.pages.iter()
+            .enumerate()
+            .map(|(index, page)| (*page, index))
+            .collect::<HashMap<u32, usize>>();
+
+        let mut successors = vec![vec![]; self.pages.len()];
+        let mut predecessors = vec![vec![]; self.pages.len()];
+
+        self.get_relevant_rules(rules).for_each(|rule| {
+            let (before, after) = (index_of_page[&rule.before], index_of_page[&rule.after]);
+            successors[before].push(after);
+            predecessors[after].push(before);
+        });
+
+        let mut predecessor_counts = predecessors.iter().map(|predecessors| predecessors.len()).collect::<Vec<usize>>();
+        let mut available = (0..self.pages.len())
+            .filter(|index| predecessor_counts[*index] == 0)
+            .collect::<BTreeSet<usize>>();
+
+        let mut pages = Vec::with_capacity(self.pages.len());
+
+        while let Some(index) = available.pop_first() {
+            pages.push(self.pages[index]);
+
+            successors[index].iter().for_each(|successor| {
+                predecessor_counts[*successor] -= 1;
+                if predecessor_counts[*successor] == 0 {
+                    available.insert(*successor);
+                }
+            });
         }
 
-        let before_index = self.pages.iter().position(|page| *page == rule.before).unwrap();
-        let after_index = self.pages.iter().position(|page| *page == rule.after).unwrap();
+        if pages.len() < self.pages.len() {
+            return Err(self.find_cycle(&predecessors, &predecessor_counts));
+        }
+
+        Ok(Manual { pages })
+    }
+
+    /// Every page left over by the topological sort has a predecessor that is left over as well, so following
+    /// the predecessors from any of them eventually runs into a cycle. The cycle starts at its page appearing
+    /// first in the manual.
+    fn find_cycle(&self, predecessors: &[Vec<usize>], predecessor_counts: &[usize]) -> Cycle {
+        let mut index = predecessor_counts.iter().position(|count| *count > 0).unwrap();
+        let mut path = vec![];
+
+        while !path.contains(&index) {
+            path.push(index);
+            index = *predecessors[index].iter().find(|predecessor| predecessor_counts[**predecessor] > 0).unwrap();
+        }
 
-        let mut new_pages = self.pages.clone();
-        new_pages.insert(before_index + 1, rule.after); // add the page one page after the containing page
-        new_pages.remove(after_index); // remove the originally misplaced page
+        let cycle_start = path.iter().position(|path_index| *path_index == index).unwrap();
+        let mut cycle = path[cycle_start..].iter().rev().copied().collect::<Vec<usize>>();
+        let first = cycle.iter().enumerate().min_by_key(|(_, index)| **index).unwrap().0;
+        cycle.rotate_left(first);
 
-        return Manual { pages: new_pages };
+        Cycle {
+            pages: cycle.iter().map(|index| self.pages[*index]).collect(),
+        }
     }
 }
 
@@ -100,3 +301,65 @@ impl FromStr for Manual {
         })
     }
 }
+
+/// Pages whose rules contradict each other: every page has to come before the next one, and the last before the first.
+struct Cycle {
+    pages: Vec<u32>,
+}
+
+impl Display for Cycle {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let pages = self.pages.iter().chain(self.pages.first()).map(|page| page.to_string()).collect::<Vec<String>>();
+        write!(f, "contradicting rules: {}", pages.join(" -> "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_rules(rules: &str) -> Vec<Rule> {
+        rules.lines().map(|rule| Rule::from_str(rule).unwrap()).collect()
+    }
+
+    fn pairs(rules: Vec<&Rule>) -> Vec<(u32, u32)> {
+        rules.iter().map(|rule| (rule.before, rule.after)).collect()
+    }
+
+    #[test]
+    fn finds_redundant_rules() {
+        let rules = parse_rules("1|2\n2|3\n1|3\n1|2");
+
+        assert_eq!(pairs(RuleSet::new(&rules).redundant_rules()), vec![(1, 3), (1, 2)]);
+    }
+
+    #[test]
+    fn does_not_flag_rules_only_implied_through_themselves() {
+        // 3 reaches 2 only via 3|1 and 1|2, so 1|2 is still needed
+        let rules = parse_rules("1|2\n1|3\n3|1");
+
+        assert_eq!(pairs(RuleSet::new(&rules).redundant_rules()), vec![]);
+    }
+
+    #[test]
+    fn reports_cycles() {
+        let rules = parse_rules("1|2\n2|3\n3|1");
+        let manual = Manual::from_str("1,2,3").unwrap();
+
+        let Err(cycle) = manual.sorted_by_rules(rules.iter()) else { panic!("expected a cycle"); };
+
+        assert_eq!(cycle.pages, vec![1, 2, 3]);
+        assert_eq!(cycle.to_string(), "contradicting rules: 1 -> 2 -> 3 -> 1");
+    }
+
+    #[test]
+    fn sorts_by_consistent_rules() {
+        let rules = parse_rules("3|1\n1|4\n5|4\n3|5");
+        let manual = Manual::from_str("4,1,5,3,2").unwrap();
+
+        let Ok(sorted) = manual.sorted_by_rules(rules.iter()) else { panic!("expected no cycle"); };
+
+        assert_eq!(sorted.pages, vec![3, 1, 5, 4, 2]);
+        assert!(sorted.satisfies_rules(rules.iter()));
+    }
+}
@@ -1,27 +1,67 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
+use std::fmt::{Display, Formatter};
 use std::str::FromStr;
 
+use crate::runner::{self, Run};
+
 #[allow(dead_code)]
 pub fn main() {
     let input = include_str!("input.txt");
 
-    let grid = Grid::from_str(input).unwrap();
+    runner::run(input, solve).print();
+}
+
+pub fn solve(input: &str, run: &mut Run) {
+    let grid = run.parse(|| Grid::from_str(input).unwrap());
 
-    println!("PART 1: {}", part1(&grid));
-    println!("PART 2: {}", part2(&grid));
+    run.part(|| part1(&grid));
+    run.part(|| part2(&grid));
 }
 
 fn part1(grid: &Grid) -> usize {
-    grid.simulate_route_length().unwrap()
+    grid.trace_route().visited_positions().len()
 }
 
 fn part2(grid: &Grid) -> usize {
-    (0..grid.width)
-        .flat_map(move |x| (0..grid.height).map(move |y| (x, y)))
-        .filter(|(x, y)| !(*x == grid.starting_position.0 && *y == grid.starting_position.1))
-        .map(|new_obstacle_position| grid.with_obstacle_at(new_obstacle_position))
-        .filter(|new_grid| new_grid.simulate_route_length().is_none())
-        .count()
+    find_loop_obstacles(grid).len()
+}
+
+/// Prints the route with every step and turn, how it ends and the obstacle positions of part 2 causing a loop.
+pub fn explain(input: &str) {
+    let grid = Grid::from_str(input).unwrap();
+    let route = grid.trace_route();
+
+    route.steps.iter().for_each(|step| println!(
+        "STEP {}: ({}, {}) {}", step.step_index, step.position.0, step.position.1, step.direction
+    ));
+
+    route.turns.iter().for_each(|turn| println!(
+        "TURN at step {}: ({}, {}) {} -> {}", turn.step_index, turn.position.0, turn.position.1, turn.from, turn.to
+    ));
+
+    match route.end {
+        RouteEnd::Exit => println!("EXIT after {} steps", route.steps.len()),
+        RouteEnd::Loop { entry, length } => println!(
+            "LOOP entered at step {}: ({}, {}) {}, length {}",
+            entry.step_index, entry.position.0, entry.position.1, entry.direction, length
+        ),
+    }
+
+    find_loop_obstacles(&grid).iter().for_each(|(x, y)| println!("LOOP OBSTACLE: ({}, {})", x, y));
+}
+
+/// Returns the positions where an added obstacle makes the guard loop, in the order the original route reaches them.
+/// An obstacle can only change the route if it is placed on the original route.
+fn find_loop_obstacles(grid: &Grid) -> Vec<(i32, i32)> {
+    let jump_table = JumpTable::new(grid);
+    let mut candidates = HashSet::from([grid.starting_position]);
+
+    grid.trace_route().steps
+        .into_iter()
+        .map(|step| step.position)
+        .filter(|position| candidates.insert(*position))
+        .filter(|position| jump_table.is_loop_with_obstacle_at(grid.starting_position, *position))
+        .collect()
 }
 
 #[derive(Debug)]
@@ -33,42 +73,152 @@ struct Grid {
 }
 
 impl Grid {
-    fn simulate_route_length(&self) -> Option<usize> {
-        let (mut x, mut y) = self.starting_position;
+    /// Follows the guard until it leaves the grid or enters a loop. A loop is detected by revisiting a position
+    /// in the same direction.
+    fn trace_route(&self) -> Route {
+        let mut position = self.starting_position;
         let mut direction = Direction::Up;
 
-        let mut visited_coordinates = HashSet::new();
-        let mut visited_states = HashSet::new();
+        let mut steps: Vec<Step> = vec![];
+        let mut turns = vec![];
+        let mut step_indices = HashMap::new();
+
+        while self.is_on_grid(position) {
+            let step_index = steps.len();
 
-        while x >= 0 && x < self.width && y >= 0 && y < self.height {
-            if visited_states.contains(&(x, y, direction)) {
-                return None; // loop detected
+            if let Some(entry_step_index) = step_indices.insert((position, direction), step_index) {
+                let entry = steps[entry_step_index];
+                return Route { steps, turns, end: RouteEnd::Loop { entry, length: step_index - entry_step_index } };
             }
 
-            visited_coordinates.insert((x, y));
-            visited_states.insert((x, y, direction));
+            steps.push(Step { position, direction, step_index });
 
-            let next_position = direction.move_in_direction((x, y));
+            let next_position = direction.move_in_direction(position);
             if self.obstacles.contains(&next_position) {
+                let from = direction;
                 direction = direction.turn_right();
+                turns.push(Turn { position, from, to: direction, step_index: step_index + 1 });
                 continue;
             }
 
-            (x, y) = next_position;
+            position = next_position;
         }
 
-        Some(visited_coordinates.len())
+        Route { steps, turns, end: RouteEnd::Exit }
     }
 
-    fn with_obstacle_at(&self, new_obstacle_position: (i32, i32)) -> Grid {
-        let mut new_obstacles = self.obstacles.clone();
-        new_obstacles.insert(new_obstacle_position);
+    fn is_on_grid(&self, (x, y): (i32, i32)) -> bool {
+        x >= 0 && x < self.width && y >= 0 && y < self.height
+    }
+}
+
+/// A state of the guard; turning in place counts as a step of its own.
+#[derive(Clone, Copy)]
+struct Step {
+    position: (i32, i32),
+    direction: Direction,
+    step_index: usize,
+}
+
+/// A turn in front of an obstacle, with `step_index` being the first step in the new direction.
+struct Turn {
+    position: (i32, i32),
+    from: Direction,
+    to: Direction,
+    step_index: usize,
+}
+
+enum RouteEnd {
+    Exit,
+    /// The guard repeats the steps from `entry` on, `length` steps per cycle.
+    Loop { entry: Step, length: usize },
+}
 
-        Grid {
-            obstacles: new_obstacles,
-            starting_position: self.starting_position,
-            width: self.width,
-            height: self.height,
+struct Route {
+    steps: Vec<Step>,
+    turns: Vec<Turn>,
+    end: RouteEnd,
+}
+
+impl Route {
+    fn visited_positions(&self) -> HashSet<(i32, i32)> {
+        self.steps.iter().map(|step| step.position).collect()
+    }
+}
+
+/// For every position and direction, the position where the guard stops in front of the next obstacle,
+/// or `None` if the guard leaves the grid. This lets the guard move from turn to turn instead of step by step.
+struct JumpTable {
+    width: i32,
+    stops: Vec<Option<(i32, i32)>>,
+}
+
+impl JumpTable {
+    fn new(grid: &Grid) -> JumpTable {
+        let mut jump_table = JumpTable {
+            width: grid.width,
+            stops: vec![None; (grid.width * grid.height) as usize * 4],
+        };
+
+        let positions = (0..grid.height)
+            .flat_map(|y| (0..grid.width).map(move |x| (x, y)))
+            .collect::<Vec<(i32, i32)>>();
+
+        for direction in [Direction::Up, Direction::Left, Direction::Right, Direction::Down] {
+            // the stop of the next position in the direction has to be known first
+            let ordered_positions: Box<dyn Iterator<Item=&(i32, i32)>> = match direction {
+                Direction::Up | Direction::Left => Box::new(positions.iter()),
+                Direction::Right | Direction::Down => Box::new(positions.iter().rev()),
+            };
+
+            for position in ordered_positions {
+                let next_position = direction.move_in_direction(*position);
+
+                let stop = if !grid.is_on_grid(next_position) {
+                    None
+                } else if grid.obstacles.contains(&next_position) {
+                    Some(*position)
+                } else {
+                    jump_table.stops[jump_table.index(next_position, direction)]
+                };
+
+                let index = jump_table.index(*position, direction);
+                jump_table.stops[index] = stop;
+            }
+        }
+
+        jump_table
+    }
+
+    fn index(&self, (x, y): (i32, i32), direction: Direction) -> usize {
+        (y * self.width + x) as usize * 4 + direction as usize
+    }
+
+    /// Simulates the route with an additional obstacle, which is overlaid on the precomputed stops.
+    /// Loops are detected by revisiting a stop in the same direction.
+    fn is_loop_with_obstacle_at(&self, starting_position: (i32, i32), obstacle: (i32, i32)) -> bool {
+        let mut position = starting_position;
+        let mut direction = Direction::Up;
+        let mut visited_stops = HashSet::new();
+
+        loop {
+            let stop = self.stops[self.index(position, direction)];
+
+            let stop = match (direction.steps_between(position, obstacle), stop) {
+                (Some(obstacle_steps), Some(stop)) if obstacle_steps <= direction.steps_between(position, stop).unwrap_or(0) =>
+                    Some(direction.move_steps(position, obstacle_steps - 1)),
+                (Some(obstacle_steps), None) => Some(direction.move_steps(position, obstacle_steps - 1)),
+                (_, stop) => stop,
+            };
+
+            let Some(stop) = stop else { return false; };
+
+            if !visited_stops.insert((stop, direction)) {
+                return true;
+            }
+
+            position = stop;
+            direction = direction.turn_right();
         }
     }
 }
@@ -118,6 +268,26 @@ impl Direction {
         }
     }
 
+    fn move_steps(&self, (x, y): (i32, i32), steps: i32) -> (i32, i32) {
+        let (dx, dy) = self.move_in_direction((0, 0));
+        (x + dx * steps, y + dy * steps)
+    }
+
+    /// Returns the number of steps in this direction from `from` to `to`, if `to` lies ahead of `from`.
+    fn steps_between(&self, from: (i32, i32), to: (i32, i32)) -> Option<i32> {
+        let (dx, dy) = self.move_in_direction((0, 0));
+
+        let steps = if dx == 0 {
+            if from.0 != to.0 { return None; }
+            (to.1 - from.1) * dy
+        } else {
+            if from.1 != to.1 { return None; }
+            (to.0 - from.0) * dx
+        };
+
+        if steps > 0 { Some(steps) } else { None }
+    }
+
     fn turn_right(&self) -> Direction {
         match self {
             Direction::Up => Direction::Right,
@@ -127,3 +297,16 @@ impl Direction {
         }
     }
 }
+
+impl Display for Direction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Direction::Up => "up",
+            Direction::Left => "left",
+            Direction::Right => "right",
+            Direction::Down => "down",
+        };
+
+        write!(f, "{}", name)
+    }
+}
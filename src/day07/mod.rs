@@ -1,63 +1,88 @@
+use std::iter::zip;
 use std::str::FromStr;
 
+use num_bigint::BigUint;
+
+use crate::runner::{self, Options, Run};
+
+const PART1_OPERATORS: [&dyn Operator; 2] = [&Add, &Multiply];
+const PART2_OPERATORS: [&dyn Operator; 3] = [&Add, &Multiply, &Concatenate { base: 10 }];
+
 #[allow(dead_code)]
 pub fn main() {
     let input = include_str!("input.txt");
 
-    let equations = input.lines()
-        .map(|line| Equation::from_str(line).unwrap())
-        .collect::<Vec<Equation>>();
-
-    println!("PART 1: {}", part1(&equations));
-    println!("PART 2: {}", part2(&equations));
+    runner::run(input, solve).print();
 }
 
-fn part1(equations: &[Equation]) -> u64 {
-    let operators = vec![Operator::Add, Operator::Multiply];
+pub fn solve(input: &str, run: &mut Run) {
+    let equations = run.parse(|| input.lines()
+        .map(|line| Equation::from_str(line).unwrap())
+        .collect::<Vec<Equation>>());
 
-    equations.iter()
-        .filter(|equation| equation.is_solvable(&operators))
-        .map(|equation| equation.result)
-        .sum()
+    run.part(|| part1(&equations));
+    run.part(|| part2(&equations));
 }
 
-fn part2(equations: &Vec<Equation>) -> u64 {
-    let operators = vec![Operator::Add, Operator::Multiply, Operator::Concatenate];
-
-    equations.iter()
-        .filter(|equation| equation.is_solvable(&operators))
-        .map(|equation| equation.result)
-        .sum()
+fn part1(equations: &[Equation]) -> BigUint {
+    Solver { operators: &PART1_OPERATORS, precedence: Precedence::LeftToRight }.sum_solvable_results(equations)
 }
 
-struct Equation {
-    result: u64,
-    numbers: Vec<u64>,
+fn part2(equations: &[Equation]) -> BigUint {
+    Solver { operators: &PART2_OPERATORS, precedence: Precedence::LeftToRight }.sum_solvable_results(equations)
 }
 
-impl Equation {
-    fn is_solvable(&self, possible_operators: &[Operator]) -> bool {
-        self.calculate_possible_results(possible_operators).contains(&self.result)
-    }
+/// Prints for every equation the operators solving it, if there are any. The operators are chosen from
+/// `--operators` (their symbols separated by commas, defaulting to the operators of part 2) and evaluated with
+/// `--precedence` (`left-to-right` as in the puzzle, or `standard`).
+pub fn explain(input: &str, options: &Options) -> Result<(), String> {
+    let operators = options.get("operators").unwrap_or("+,*,||")
+        .split(',')
+        .map(parse_operator)
+        .collect::<Result<Vec<Box<dyn Operator>>, String>>()?;
 
-    fn calculate_possible_results(&self, possible_operators: &[Operator]) -> Vec<u64> {
-        Self::calculate_possible_results_with(vec![self.numbers[0]], &self.numbers[1..], possible_operators)
-    }
+    let precedence = match options.get("precedence") {
+        None | Some("left-to-right") => Precedence::LeftToRight,
+        Some("standard") => Precedence::Standard,
+        Some(precedence) => return Err(format!("unknown precedence: {}", precedence)),
+    };
 
-    fn calculate_possible_results_with(intermediate_results: Vec<u64>, remaining_numbers: &[u64], possible_operators: &[Operator]) -> Vec<u64> {
-        if remaining_numbers.is_empty() {
-            return intermediate_results;
-        }
+    let operators = operators.iter().map(|operator| operator.as_ref()).collect::<Vec<&dyn Operator>>();
+    let solver = Solver { operators: &operators, precedence };
 
-        let results = intermediate_results.iter()
-            .flat_map(|intermediate_result| possible_operators.iter()
-                .map(|operator| operator.apply(*intermediate_result, remaining_numbers[0])))
-            .collect();
+    input.lines()
+        .map(|line| Equation::from_str(line).unwrap())
+        .enumerate()
+        .for_each(|(index, equation)| match solver.find_operators(&equation) {
+            Some(operators) => println!("EQUATION {}: {}", index + 1, solver.format(&equation, &operators)),
+            None => println!("EQUATION {}: {} is unsolvable", index + 1, equation.result),
+        });
+
+    Ok(())
+}
 
-        Self::calculate_possible_results_with(results, &remaining_numbers[1..], possible_operators)
+/// Parses an operator from its symbol, e.g. `||2` for concatenation in base 2.
+fn parse_operator(symbol: &str) -> Result<Box<dyn Operator>, String> {
+    match symbol {
+        "+" => Ok(Box::new(Add)),
+        "-" => Ok(Box::new(Subtract)),
+        "*" => Ok(Box::new(Multiply)),
+        "/" => Ok(Box::new(ExactDivide)),
+        "^" => Ok(Box::new(Power)),
+        "||" => Ok(Box::new(Concatenate { base: 10 })),
+        _ => symbol.strip_prefix("||")
+            .and_then(|base| base.parse::<u32>().ok())
+            .filter(|base| *base >= 2)
+            .map(|base| Box::new(Concatenate { base }) as Box<dyn Operator>)
+            .ok_or(format!("unknown operator: {}", symbol)),
     }
 }
 
+struct Equation {
+    result: BigUint,
+    numbers: Vec<BigUint>,
+}
+
 impl FromStr for Equation {
     type Err = ();
 
@@ -71,18 +96,458 @@ impl FromStr for Equation {
     }
 }
 
-enum Operator {
-    Add,
-    Multiply,
-    Concatenate,
+enum Precedence {
+    /// Every operator is applied in order, as in the puzzle.
+    LeftToRight,
+    /// Operators of higher precedence are applied first, operators of equal precedence from left to right, unless
+    /// they are right-associative like `^`.
+    Standard,
 }
 
-impl Operator {
-    fn apply(&self, left: u64, right: u64) -> u64 {
-        match &self {
-            Operator::Add => left + right,
-            Operator::Multiply => left * right,
-            Operator::Concatenate => left * 10u64.pow(right.ilog10() + 1) + right,
+/// Finds the operators solving equations, choosing from its operator set and evaluating with its precedence mode.
+struct Solver<'a> {
+    operators: &'a [&'a dyn Operator],
+    precedence: Precedence,
+}
+
+impl<'a> Solver<'a> {
+    fn sum_solvable_results(&self, equations: &[Equation]) -> BigUint {
+        let results = equations.iter()
+            .filter(|equation| self.find_operators(equation).is_some())
+            .map(|equation| &equation.result);
+
+        results.sum()
+    }
+
+    /// Returns the operators to put between the numbers to get the result.
+    fn find_operators(&self, equation: &Equation) -> Option<Vec<&'a dyn Operator>> {
+        match self.precedence {
+            Precedence::LeftToRight => self.find_operators_backwards(&equation.result, &equation.numbers),
+            Precedence::Standard => self.find_operators_exhaustively(&equation.numbers, |result| *result == equation.result),
+        }
+    }
+
+    /// Works backwards from the result: the last number must have been combined with the result of the numbers before
+    /// it, so every operator that can be undone gives a smaller equation to solve. Undoing fails for most operators,
+    /// e.g. when the last number is not a divisor, not a suffix or larger than the result, which prunes the search.
+    /// This only works if the operators are applied from left to right.
+    fn find_operators_backwards(&self, result: &BigUint, numbers: &[BigUint]) -> Option<Vec<&'a dyn Operator>> {
+        let (last_number, remaining_numbers) = numbers.split_last()?;
+
+        if remaining_numbers.is_empty() {
+            return if result == last_number { Some(vec![]) } else { None };
+        }
+
+        self.operators.iter().find_map(|operator| {
+            let mut operators = if operator.absorbs(result, last_number) {
+                // the numbers before can result in anything, as long as they can be evaluated at all
+                self.find_operators_exhaustively(remaining_numbers, |_| true)?
+            } else {
+                let intermediate_result = operator.undo(result, last_number)?;
+                self.find_operators_backwards(&intermediate_result, remaining_numbers)?
+            };
+
+            operators.push(*operator);
+            Some(operators)
+        })
+    }
+
+    /// Tries every combination of operators, which works with any precedence mode, but evaluates up to
+    /// |operators|^(n-1) combinations.
+    fn find_operators_exhaustively(&self, numbers: &[BigUint], is_accepted: impl Fn(&BigUint) -> bool) -> Option<Vec<&'a dyn Operator>> {
+        let operator_count = numbers.len().saturating_sub(1);
+        if operator_count > 0 && self.operators.is_empty() {
+            return None;
+        }
+
+        let mut indices = vec![0; operator_count];
+
+        loop {
+            let operators = indices.iter().map(|index| self.operators[*index]).collect::<Vec<&dyn Operator>>();
+            if self.evaluate(numbers, &operators).is_some_and(|result| is_accepted(&result)) {
+                return Some(operators);
+            }
+
+            // count up like an odometer, the last operator changing fastest
+            let position = indices.iter().rposition(|index| index + 1 < self.operators.len())?;
+            indices[position] += 1;
+            indices[position + 1..].fill(0);
+        }
+    }
+
+    /// Returns `None` if an operator is undefined for its operands.
+    fn evaluate(&self, numbers: &[BigUint], operators: &[&dyn Operator]) -> Option<BigUint> {
+        let (first_number, remaining_numbers) = numbers.split_first()?;
+
+        match self.precedence {
+            Precedence::LeftToRight => zip(operators, remaining_numbers)
+                .try_fold(first_number.clone(), |left, (operator, right)| operator.apply(&left, right)),
+            Precedence::Standard => {
+                // operators are applied once an operator of lower precedence follows them, or of equal precedence
+                // if that one is left-associative
+                let mut operands = vec![first_number.clone()];
+                let mut pending_operators: Vec<&dyn Operator> = vec![];
+
+                for (operator, number) in zip(operators, remaining_numbers) {
+                    while let Some(pending_operator) = pending_operators.pop_if(|pending_operator| pending_operator.precedence() > operator.precedence()
+                        || (pending_operator.precedence() == operator.precedence() && !operator.is_right_associative())) {
+                        apply_to_operands(pending_operator, &mut operands)?;
+                    }
+
+                    pending_operators.push(*operator);
+                    operands.push(number.clone());
+                }
+
+                while let Some(pending_operator) = pending_operators.pop() {
+                    apply_to_operands(pending_operator, &mut operands)?;
+                }
+
+                operands.pop()
+            }
         }
     }
+
+    /// Writes the equation with the operators, where the result is evaluated from them as a cross-check.
+    fn format(&self, equation: &Equation, operators: &[&dyn Operator]) -> String {
+        let terms = zip(operators, &equation.numbers[1..])
+            .map(|(operator, number)| format!(" {} {}", operator.symbol(), number))
+            .collect::<String>();
+
+        let result = self.evaluate(&equation.numbers, operators)
+            .map(|result| result.to_string())
+            .unwrap_or(String::from("undefined"));
+
+        format!("{} = {}{}", result, equation.numbers[0], terms)
+    }
+}
+
+/// Replaces the last two operands with the result of the operator.
+fn apply_to_operands(operator: &dyn Operator, operands: &mut Vec<BigUint>) -> Option<()> {
+    let right = operands.pop()?;
+    let left = operands.pop()?;
+
+    operands.push(operator.apply(&left, &right)?);
+    Some(())
+}
+
+/// A binary operator on non-negative integers of any size. Operators which are undefined for some operands, e.g.
+/// subtraction resulting in a negative number, return `None` for them.
+trait Operator {
+    fn symbol(&self) -> String;
+
+    /// Operators of higher precedence are applied first, if the solver respects precedence.
+    fn precedence(&self) -> u8;
+
+    /// Whether a chain of this operator is grouped from the right, like `2 ^ 3 ^ 2 = 2 ^ 9`, if the solver respects
+    /// precedence.
+    fn is_right_associative(&self) -> bool {
+        false
+    }
+
+    fn apply(&self, left: &BigUint, right: &BigUint) -> Option<BigUint>;
+
+    /// Returns the left operand for which applying the operator with the right operand gives the result, if there is one.
+    fn undo(&self, result: &BigUint, right: &BigUint) -> Option<BigUint>;
+
+    /// Whether applying the operator with the right operand gives the result for any left operand, like `x * 0 = 0`.
+    fn absorbs(&self, _result: &BigUint, _right: &BigUint) -> bool {
+        false
+    }
+}
+
+struct Add;
+
+impl Operator for Add {
+    fn symbol(&self) -> String {
+        String::from("+")
+    }
+
+    fn precedence(&self) -> u8 {
+        1
+    }
+
+    fn apply(&self, left: &BigUint, right: &BigUint) -> Option<BigUint> {
+        Some(left + right)
+    }
+
+    fn undo(&self, result: &BigUint, right: &BigUint) -> Option<BigUint> {
+        if result >= right { Some(result - right) } else { None }
+    }
+}
+
+struct Subtract;
+
+impl Operator for Subtract {
+    fn symbol(&self) -> String {
+        String::from("-")
+    }
+
+    fn precedence(&self) -> u8 {
+        1
+    }
+
+    fn apply(&self, left: &BigUint, right: &BigUint) -> Option<BigUint> {
+        if left >= right { Some(left - right) } else { None }
+    }
+
+    fn undo(&self, result: &BigUint, right: &BigUint) -> Option<BigUint> {
+        Some(result + right)
+    }
+}
+
+struct Multiply;
+
+impl Operator for Multiply {
+    fn symbol(&self) -> String {
+        String::from("*")
+    }
+
+    fn precedence(&self) -> u8 {
+        2
+    }
+
+    fn apply(&self, left: &BigUint, right: &BigUint) -> Option<BigUint> {
+        Some(left * right)
+    }
+
+    fn undo(&self, result: &BigUint, right: &BigUint) -> Option<BigUint> {
+        if *right != BigUint::ZERO && (result % right) == BigUint::ZERO { Some(result / right) } else { None }
+    }
+
+    fn absorbs(&self, result: &BigUint, right: &BigUint) -> bool {
+        *right == BigUint::ZERO && *result == BigUint::ZERO
+    }
+}
+
+/// Division without a remainder.
+struct ExactDivide;
+
+impl Operator for ExactDivide {
+    fn symbol(&self) -> String {
+        String::from("/")
+    }
+
+    fn precedence(&self) -> u8 {
+        2
+    }
+
+    fn apply(&self, left: &BigUint, right: &BigUint) -> Option<BigUint> {
+        if *right != BigUint::ZERO && (left % right) == BigUint::ZERO { Some(left / right) } else { None }
+    }
+
+    fn undo(&self, result: &BigUint, right: &BigUint) -> Option<BigUint> {
+        if *right != BigUint::ZERO { Some(result * right) } else { None }
+    }
+}
+
+struct Power;
+
+impl Operator for Power {
+    fn symbol(&self) -> String {
+        String::from("^")
+    }
+
+    fn precedence(&self) -> u8 {
+        3
+    }
+
+    fn is_right_associative(&self) -> bool {
+        true
+    }
+
+    /// Results of more than `MAX_BITS` bits are undefined, as they could exhaust the memory.
+    fn apply(&self, left: &BigUint, right: &BigUint) -> Option<BigUint> {
+        const MAX_BITS: u64 = 1 << 20;
+
+        if *left <= BigUint::from(1u32) {
+            return Some(if *right == BigUint::ZERO { BigUint::from(1u32) } else { left.clone() });
+        }
+
+        let exponent = u32::try_from(right).ok()?;
+        if left.bits().saturating_mul(exponent as u64) > MAX_BITS {
+            return None;
+        }
+
+        Some(left.pow(exponent))
+    }
+
+    fn undo(&self, result: &BigUint, right: &BigUint) -> Option<BigUint> {
+        let exponent = u32::try_from(right).ok().filter(|exponent| *exponent > 0)?;
+        let root = result.nth_root(exponent);
+
+        if root.pow(exponent) == *result { Some(root) } else { None }
+    }
+
+    fn absorbs(&self, result: &BigUint, right: &BigUint) -> bool {
+        *right == BigUint::ZERO && *result == BigUint::from(1u32)
+    }
+}
+
+/// Appends the digits of the right operand to the left operand, both written in `base`.
+struct Concatenate {
+    base: u32,
+}
+
+impl Operator for Concatenate {
+    fn symbol(&self) -> String {
+        if self.base == 10 { String::from("||") } else { format!("||{}", self.base) }
+    }
+
+    fn precedence(&self) -> u8 {
+        4
+    }
+
+    fn apply(&self, left: &BigUint, right: &BigUint) -> Option<BigUint> {
+        Some(left * self.shift(right) + right)
+    }
+
+    fn undo(&self, result: &BigUint, right: &BigUint) -> Option<BigUint> {
+        let shift = self.shift(right);
+        if (result % &shift) == *right { Some(result / shift) } else { None }
+    }
+}
+
+impl Concatenate {
+    /// The factor moving the left operand past the digits of the right operand; zero has a single digit as well.
+    fn shift(&self, right: &BigUint) -> BigUint {
+        let mut shift = BigUint::from(self.base);
+        while shift <= *right {
+            shift *= self.base;
+        }
+
+        shift
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn numbers(numbers: &[u32]) -> Vec<BigUint> {
+        numbers.iter().map(|number| BigUint::from(*number)).collect()
+    }
+
+    fn apply(operator: &dyn Operator, left: u32, right: u32) -> Option<BigUint> {
+        operator.apply(&BigUint::from(left), &BigUint::from(right))
+    }
+
+    fn undo(operator: &dyn Operator, result: u32, right: u32) -> Option<BigUint> {
+        operator.undo(&BigUint::from(result), &BigUint::from(right))
+    }
+
+    #[test]
+    fn evaluates_with_precedence_modes() {
+        let operators: [&dyn Operator; 2] = [&Add, &Multiply];
+
+        let left_to_right = Solver { operators: &operators, precedence: Precedence::LeftToRight };
+        let standard = Solver { operators: &operators, precedence: Precedence::Standard };
+
+        assert_eq!(left_to_right.evaluate(&numbers(&[2, 3, 4]), &[&Add, &Multiply]), Some(BigUint::from(20u32)));
+        assert_eq!(standard.evaluate(&numbers(&[2, 3, 4]), &[&Add, &Multiply]), Some(BigUint::from(14u32)));
+        assert_eq!(standard.evaluate(&numbers(&[2, 3, 4]), &[&Multiply, &Add]), Some(BigUint::from(10u32)));
+    }
+
+    #[test]
+    fn solves_equations_beyond_u64() {
+        let solver = Solver { operators: &PART2_OPERATORS, precedence: Precedence::LeftToRight };
+        let numbers = numbers(&[7, 13, 2, 98, 5, 41, 3, 66, 9, 12, 4, 87, 6, 35, 8, 21, 17, 3, 54, 2, 11, 76, 9, 5]);
+        let chosen_operators = (0..numbers.len() - 1)
+            .map(|index| PART2_OPERATORS[1 + index % 2])
+            .collect::<Vec<&dyn Operator>>();
+
+        let result = solver.evaluate(&numbers, &chosen_operators).unwrap();
+        assert!(result > BigUint::from(u64::MAX) * u32::MAX);
+
+        let equation = Equation { result: result.clone(), numbers: numbers.clone() };
+        let solution = solver.find_operators(&equation).unwrap();
+
+        assert_eq!(solver.evaluate(&numbers, &solution), Some(result));
+    }
+
+    #[test]
+    fn solves_with_precedence_modes() {
+        let operators: [&dyn Operator; 2] = [&Add, &Multiply];
+        let equation = Equation::from_str("14: 2 3 4").unwrap();
+
+        let left_to_right = Solver { operators: &operators, precedence: Precedence::LeftToRight };
+        let standard = Solver { operators: &operators, precedence: Precedence::Standard };
+
+        assert!(left_to_right.find_operators(&equation).is_none());
+
+        let solution = standard.find_operators(&equation).unwrap();
+        assert_eq!(solution.iter().map(|operator| operator.symbol()).collect::<Vec<String>>(), vec!["+", "*"]);
+    }
+
+    #[test]
+    fn groups_power_from_the_right() {
+        let operators: [&dyn Operator; 1] = [&Power];
+        let standard = Solver { operators: &operators, precedence: Precedence::Standard };
+        let left_to_right = Solver { operators: &operators, precedence: Precedence::LeftToRight };
+
+        assert_eq!(standard.evaluate(&numbers(&[2, 3, 2]), &[&Power, &Power]), Some(BigUint::from(512u32)));
+        assert_eq!(left_to_right.evaluate(&numbers(&[2, 3, 2]), &[&Power, &Power]), Some(BigUint::from(64u32)));
+    }
+
+    #[test]
+    fn applies_and_undoes_add_and_multiply() {
+        assert_eq!(apply(&Add, 5, 3), Some(BigUint::from(8u32)));
+        assert_eq!(undo(&Add, 8, 3), Some(BigUint::from(5u32)));
+        assert_eq!(undo(&Add, 2, 3), None);
+
+        assert_eq!(apply(&Multiply, 5, 3), Some(BigUint::from(15u32)));
+        assert_eq!(undo(&Multiply, 15, 3), Some(BigUint::from(5u32)));
+        assert_eq!(undo(&Multiply, 16, 3), None);
+        assert_eq!(undo(&Multiply, 0, 0), None);
+        assert!(Multiply.absorbs(&BigUint::ZERO, &BigUint::ZERO));
+    }
+
+    #[test]
+    fn applies_and_undoes_subtract() {
+        assert_eq!(apply(&Subtract, 5, 3), Some(BigUint::from(2u32)));
+        assert_eq!(apply(&Subtract, 3, 5), None);
+        assert_eq!(undo(&Subtract, 2, 3), Some(BigUint::from(5u32)));
+    }
+
+    #[test]
+    fn applies_and_undoes_exact_divide() {
+        assert_eq!(apply(&ExactDivide, 8, 4), Some(BigUint::from(2u32)));
+        assert_eq!(apply(&ExactDivide, 9, 4), None);
+        assert_eq!(apply(&ExactDivide, 8, 0), None);
+        assert_eq!(undo(&ExactDivide, 2, 4), Some(BigUint::from(8u32)));
+        assert_eq!(undo(&ExactDivide, 2, 0), None);
+    }
+
+    #[test]
+    fn applies_and_undoes_power() {
+        assert_eq!(apply(&Power, 2, 10), Some(BigUint::from(1024u32)));
+        assert_eq!(apply(&Power, 7, 0), Some(BigUint::from(1u32)));
+        assert_eq!(apply(&Power, 0, 0), Some(BigUint::from(1u32)));
+        assert_eq!(undo(&Power, 1024, 10), Some(BigUint::from(2u32)));
+        assert_eq!(undo(&Power, 1000, 2), None);
+        assert!(Power.absorbs(&BigUint::from(1u32), &BigUint::ZERO));
+    }
+
+    #[test]
+    fn applies_and_undoes_concatenate() {
+        assert_eq!(apply(&Concatenate { base: 10 }, 12, 345), Some(BigUint::from(12345u32)));
+        assert_eq!(apply(&Concatenate { base: 10 }, 12, 0), Some(BigUint::from(120u32)));
+        assert_eq!(undo(&Concatenate { base: 10 }, 12345, 345), Some(BigUint::from(12u32)));
+        assert_eq!(undo(&Concatenate { base: 10 }, 120, 0), Some(BigUint::from(12u32)));
+        assert_eq!(undo(&Concatenate { base: 10 }, 12345, 45), Some(BigUint::from(123u32)));
+        assert_eq!(undo(&Concatenate { base: 10 }, 12345, 44), None);
+
+        // 1 || 10 in base 2 is 110
+        assert_eq!(apply(&Concatenate { base: 2 }, 1, 2), Some(BigUint::from(6u32)));
+        assert_eq!(undo(&Concatenate { base: 2 }, 6, 2), Some(BigUint::from(1u32)));
+    }
+
+    #[test]
+    fn parses_operators() {
+        for symbol in ["+", "-", "*", "/", "^", "||", "||2"] {
+            assert_eq!(parse_operator(symbol).unwrap().symbol(), symbol);
+        }
+
+        assert!(parse_operator("||1").is_err());
+        assert!(parse_operator("%").is_err());
+    }
 }
@@ -1,69 +1,128 @@
 use std::collections::{HashMap, HashSet};
+use std::iter::zip;
+use std::ops::RangeInclusive;
 use std::str::FromStr;
 
+use crate::runner::{self, Options, Run};
+
 #[allow(dead_code)]
 pub fn main() {
     let input = include_str!("input.txt");
 
-    let map = Map::from_str(input).unwrap();
+    runner::run(input, solve).print();
+}
+
+pub fn solve(input: &str, run: &mut Run) {
+    let map = run.parse(|| Map::from_str(input).unwrap());
 
-    println!("PART 1: {}", part1(&map));
-    println!("PART 2: {}", part2(&map));
+    run.part(|| part1(&map));
+    run.part(|| part2(&map));
 }
 
 fn part1(map: &Map) -> usize {
-    map.get_combinations_of_antennas_with_same_frequency()
-        .flat_map(|(antenna, other_antenna)| {
-            let dx = other_antenna.x - antenna.x;
-            let dy = other_antenna.y - antenna.y;
-
-            vec![
-                (antenna.x - dx, antenna.y - dy),
-                (other_antenna.x + dx, other_antenna.y + dy),
-            ]
-        })
-        .filter(|(x, y)| map.is_on_map(*x, *y))
-        .collect::<HashSet<(i32, i32)>>()
-        .len()
+    count_antinode_positions(&map.find_antinodes(&Harmonics::DoubleDistance, &Interpretation::Puzzle))
 }
 
 fn part2(map: &Map) -> usize {
-    map.get_combinations_of_antennas_with_same_frequency()
-        .flat_map(|(antenna, other_antenna)| {
-            let dx = other_antenna.x - antenna.x;
-            let dy = other_antenna.y - antenna.y;
+    count_antinode_positions(&map.find_antinodes(&Harmonics::InLine, &Interpretation::Puzzle))
+}
 
-            let mut locations: HashSet<(i32, i32)> = HashSet::new();
+/// Prints for both parts every antinode per frequency and the pairs of antennas it is caused by, followed by the
+/// antinodes of part 2 which are only found when every grid point in line counts. With `--harmonics K1..K2`, the
+/// same is printed for the multiples `K1` to `K2` (inclusive) of the distance instead of for both parts.
+pub fn explain(input: &str, options: &Options) -> Result<(), String> {
+    let map = Map::from_str(input).unwrap();
 
-            {
-                let mut x = antenna.x;
-                let mut y = antenna.y;
+    let explained_harmonics = match options.get("harmonics") {
+        None => vec![(String::from("PART 1"), Harmonics::DoubleDistance), (String::from("PART 2"), Harmonics::InLine)],
+        Some(multiples) => vec![(format!("MULTIPLES {}", multiples), parse_multiples(multiples)?)],
+    };
 
-                while map.is_on_map(x, y) {
-                    locations.insert((x, y));
-                    x += dx;
-                    y += dy;
-                }
-            }
+    for (label, harmonics) in &explained_harmonics {
+        for frequency_antinodes in map.find_antinodes(harmonics, &Interpretation::Puzzle) {
+            let mut positions = frequency_antinodes.antinodes.keys().collect::<Vec<&(i32, i32)>>();
+            positions.sort_by_key(|(x, y)| (*y, *x));
 
-            {
-                let mut x = antenna.x;
-                let mut y = antenna.y;
+            positions.iter().for_each(|position| println!(
+                "{} FREQUENCY {}: ({}, {}) caused by {}",
+                label, frequency_antinodes.frequency, position.0, position.1,
+                frequency_antinodes.antinodes[position].iter()
+                    .map(|(antenna, other_antenna)| format!("({}, {})-({}, {})", antenna.x, antenna.y, other_antenna.x, other_antenna.y))
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            ));
+        }
+    }
 
-                while map.is_on_map(x, y) {
-                    locations.insert((x, y));
-                    x -= dx;
-                    y -= dy;
-                }
-            }
+    let (label, harmonics) = explained_harmonics.last().unwrap();
+    let puzzle_antinodes = map.find_antinodes(harmonics, &Interpretation::Puzzle);
+    for (frequency_antinodes, puzzle_frequency_antinodes) in zip(map.find_antinodes(harmonics, &Interpretation::Geometric), &puzzle_antinodes) {
+        let mut positions = frequency_antinodes.antinodes.keys()
+            .filter(|position| !puzzle_frequency_antinodes.antinodes.contains_key(position))
+            .collect::<Vec<&(i32, i32)>>();
+        positions.sort_by_key(|(x, y)| (*y, *x));
+
+        positions.iter().for_each(|(x, y)| println!(
+            "{} FREQUENCY {}: ({}, {}) only in line geometrically", label, frequency_antinodes.frequency, x, y
+        ));
+    }
+
+    Ok(())
+}
+
+/// Parses a range of multiples like `1..3`, which includes its end.
+fn parse_multiples(multiples: &str) -> Result<Harmonics, String> {
+    multiples.split_once("..")
+        .and_then(|(start, end)| Some((start.parse::<i32>().ok()?, end.parse::<i32>().ok()?)))
+        .filter(|(start, end)| 0 <= *start && start <= end)
+        .map(|(start, end)| Harmonics::Multiples(start..=end))
+        .ok_or(format!("invalid harmonics: {}", multiples))
+}
 
-            locations
-        })
-        .filter(|(x, y)| map.is_on_map(*x, *y))
-        .collect::<HashSet<(i32, i32)>>()
+/// Counts the positions with an antinode of any frequency.
+fn count_antinode_positions(antinodes: &[FrequencyAntinodes]) -> usize {
+    antinodes.iter()
+        .flat_map(|frequency_antinodes| frequency_antinodes.antinodes.keys())
+        .collect::<HashSet<&(i32, i32)>>()
         .len()
 }
 
+/// Where antinodes of a pair of antennas are, as multiples `k` of the distance between them: on the line through
+/// both antennas, `k` times the distance away from one antenna, on the side of the other one.
+enum Harmonics {
+    /// Only `k = 2`, i.e. twice as far from one antenna as from the other, as in part 1.
+    DoubleDistance,
+    /// Every `k >= 0` on the map, including the antennas themselves, as in part 2.
+    InLine,
+    /// Every `k` of the range that is on the map. The range must not include negative multiples.
+    Multiples(RangeInclusive<i32>),
+}
+
+impl Harmonics {
+    fn multiples(&self) -> RangeInclusive<i32> {
+        match self {
+            Harmonics::DoubleDistance => 2..=2,
+            Harmonics::InLine => 0..=i32::MAX,
+            Harmonics::Multiples(multiples) => multiples.clone(),
+        }
+    }
+}
+
+/// Which points count as in line with a pair of antennas.
+enum Interpretation {
+    /// Only points at whole multiples of the distance between the antennas, as in the puzzle.
+    Puzzle,
+    /// Every grid point on the line: the distance is divided into `gcd(dx, dy)` steps, which hits the grid points
+    /// between multiples of the distance as well. Multiples `k` of the distance then include fractions of `1 / gcd`.
+    Geometric,
+}
+
+/// The antinodes of a frequency, each with the pairs of antennas causing it.
+struct FrequencyAntinodes<'a> {
+    frequency: char,
+    antinodes: HashMap<(i32, i32), Vec<(&'a Antenna, &'a Antenna)>>,
+}
+
 struct Map {
     width: i32,
     height: i32,
@@ -75,23 +134,62 @@ impl Map {
         x >= 0 && x < self.width && y >= 0 && y < self.height
     }
 
-    fn get_combinations_of_antennas_with_same_frequency(&self) -> impl Iterator<Item=(&Antenna, &Antenna)> {
-        self.antennas_per_frequency.iter()
-            .flat_map(|(_, antennas)| {
-                let mut combinations = vec![];
+    /// Returns the antinodes on the map per frequency, ordered by frequency.
+    fn find_antinodes(&self, harmonics: &Harmonics, interpretation: &Interpretation) -> Vec<FrequencyAntinodes<'_>> {
+        let mut antinodes = self.antennas_per_frequency.iter()
+            .map(|(frequency, antennas)| {
+                let mut antinodes: HashMap<(i32, i32), Vec<(&Antenna, &Antenna)>> = HashMap::new();
 
-                for i in 0..antennas.len() {
-                    for j in (i + 1)..antennas.len() {
-                        let (antenna, other_antenna) = (&antennas[i], &antennas[j]);
-                        combinations.push((antenna, other_antenna));
+                for (antenna, other_antenna) in get_combinations(antennas) {
+                    let dx = other_antenna.x - antenna.x;
+                    let dy = other_antenna.y - antenna.y;
+
+                    let steps_per_distance = match interpretation {
+                        Interpretation::Puzzle => 1,
+                        Interpretation::Geometric => gcd(dx.abs(), dy.abs()),
+                    };
+                    let (step_x, step_y) = (dx / steps_per_distance, dy / steps_per_distance);
+
+                    let multiples = harmonics.multiples();
+                    let steps = multiples.start().saturating_mul(steps_per_distance)..=multiples.end().saturating_mul(steps_per_distance);
+
+                    for (origin, sign) in [(antenna, 1), (other_antenna, -1)] {
+                        // steps only move further away from the origin, so the first one off the map (or beyond the
+                        // range of an i32) ends the line
+                        steps.clone()
+                            .map_while(|step| Some((
+                                origin.x.checked_add(step.checked_mul(sign * step_x)?)?,
+                                origin.y.checked_add(step.checked_mul(sign * step_y)?)?,
+                            )))
+                            .take_while(|(x, y)| self.is_on_map(*x, *y))
+                            .for_each(|position| {
+                                let pairs = antinodes.entry(position).or_default();
+                                if !pairs.contains(&(antenna, other_antenna)) {
+                                    pairs.push((antenna, other_antenna));
+                                }
+                            });
                     }
                 }
 
-                combinations
+                FrequencyAntinodes { frequency: *frequency, antinodes }
             })
+            .collect::<Vec<FrequencyAntinodes>>();
+
+        antinodes.sort_by_key(|frequency_antinodes| frequency_antinodes.frequency);
+        antinodes
     }
 }
 
+fn gcd(a: i32, b: i32) -> i32 {
+    if b == 0 { a } else { gcd(b, a % b) }
+}
+
+fn get_combinations(antennas: &[Antenna]) -> impl Iterator<Item=(&Antenna, &Antenna)> {
+    antennas.iter()
+        .enumerate()
+        .flat_map(|(i, antenna)| antennas[(i + 1)..].iter().map(move |other_antenna| (antenna, other_antenna)))
+}
+
 impl FromStr for Map {
     type Err = ();
 
@@ -112,8 +210,73 @@ impl FromStr for Map {
     }
 }
 
+#[derive(PartialEq)]
 struct Antenna {
     x: i32,
     y: i32,
     frequency: char,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Antennas at (0, 0) and (2, 4), whose offset has a gcd of 2.
+    const MAP: &str = "a....\n.....\n.....\n.....\n..a..\n.....\n.....\n.....\n.....";
+
+    fn antinode_positions(harmonics: &Harmonics, interpretation: &Interpretation) -> Vec<(i32, i32)> {
+        let map = Map::from_str(MAP).unwrap();
+
+        let mut positions = map.find_antinodes(harmonics, interpretation).iter()
+            .flat_map(|frequency_antinodes| frequency_antinodes.antinodes.keys().copied())
+            .collect::<Vec<(i32, i32)>>();
+        positions.sort();
+        positions
+    }
+
+    #[test]
+    fn puzzle_interpretation_skips_intermediate_points() {
+        assert_eq!(antinode_positions(&Harmonics::InLine, &Interpretation::Puzzle), vec![(0, 0), (2, 4), (4, 8)]);
+    }
+
+    #[test]
+    fn geometric_interpretation_includes_intermediate_points() {
+        assert_eq!(
+            antinode_positions(&Harmonics::InLine, &Interpretation::Geometric),
+            vec![(0, 0), (1, 2), (2, 4), (3, 6), (4, 8)]
+        );
+    }
+
+    #[test]
+    fn double_distance_is_the_same_in_both_interpretations() {
+        assert_eq!(antinode_positions(&Harmonics::DoubleDistance, &Interpretation::Puzzle), vec![(4, 8)]);
+        assert_eq!(antinode_positions(&Harmonics::DoubleDistance, &Interpretation::Geometric), vec![(4, 8)]);
+    }
+
+    #[test]
+    fn multiples_are_limited_to_their_range() {
+        // (1, 2) is half the distance away from either antenna, so it is not among the multiples 1 to 3
+        assert_eq!(antinode_positions(&Harmonics::Multiples(1..=3), &Interpretation::Puzzle), vec![(0, 0), (2, 4), (4, 8)]);
+        assert_eq!(
+            antinode_positions(&Harmonics::Multiples(1..=3), &Interpretation::Geometric),
+            vec![(0, 0), (2, 4), (3, 6), (4, 8)]
+        );
+        assert_eq!(antinode_positions(&Harmonics::Multiples(i32::MAX..=i32::MAX), &Interpretation::Geometric), vec![]);
+    }
+
+    #[test]
+    fn parses_multiples() {
+        assert!(matches!(parse_multiples("1..3"), Ok(Harmonics::Multiples(multiples)) if multiples == (1..=3)));
+        assert!(parse_multiples("3..1").is_err());
+        assert!(parse_multiples("-1..3").is_err());
+        assert!(parse_multiples("0..3000000000").is_err());
+    }
+
+    #[test]
+    fn reduces_steps_by_gcd() {
+        assert_eq!(gcd(2, 4), 2);
+        assert_eq!(gcd(6, 9), 3);
+        assert_eq!(gcd(0, 5), 5);
+        assert_eq!(gcd(7, 0), 7);
+    }
+}
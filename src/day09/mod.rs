@@ -1,75 +1,228 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+use std::fmt::{Display, Formatter};
 use std::str::FromStr;
 
+use crate::runner::{self, Run};
+
 #[allow(dead_code)]
 pub fn main() {
     let input = include_str!("input.txt");
 
-    let disk_map = DiskMap::from_str(input).unwrap();
+    runner::run(input, solve).print();
+}
+
+pub fn solve(input: &str, run: &mut Run) {
+    let disk_map = run.parse(|| DiskMap::from_str(input).unwrap());
 
-    println!("PART 1: {}", part1(disk_map.clone()));
-    println!("PART 2: {}", part2(disk_map));
+    run.part(|| part1(disk_map.clone()));
+    run.part(|| part2(disk_map));
 }
 
 fn part1(mut disk_map: DiskMap) -> usize {
-    loop {
-        let mut region = disk_map.regions.pop().unwrap();
-        if region.id.is_none() {
-            continue;
+    BlockLevel.compact(&mut disk_map);
+    disk_map.checksum()
+}
+
+fn part2(mut disk_map: DiskMap) -> usize {
+    WholeFile { fit: Fit::First }.compact(&mut disk_map);
+    disk_map.checksum()
+}
+
+/// Prints the checksum and the fragmentation after compacting with each policy, and the layout for small disk maps.
+pub fn explain(input: &str) {
+    let disk_map = DiskMap::from_str(input).unwrap();
+
+    if let Some(layout) = disk_map.render_layout() {
+        println!("INITIAL: {}", layout);
+    }
+
+    let policies: [&dyn Policy; 4] = [
+        &BlockLevel,
+        &WholeFile { fit: Fit::First },
+        &WholeFile { fit: Fit::Best },
+        &WholeFile { fit: Fit::Worst },
+    ];
+
+    for policy in policies {
+        let mut disk_map = disk_map.clone();
+        policy.compact(&mut disk_map);
+
+        println!("{}: checksum {}, {}", policy.name().to_uppercase(), disk_map.checksum(), disk_map.fragmentation());
+        if let Some(layout) = disk_map.render_layout() {
+            println!("  {}", layout);
         }
+    }
+}
+
+/// A way of moving files from the end of the disk into free space further left.
+trait Policy {
+    fn name(&self) -> String;
 
-        let mut reordering_successful = false;
+    fn compact(&self, disk_map: &mut DiskMap);
+}
+
+/// Moves single blocks from the end of the disk into the leftmost free block, as in part 1.
+struct BlockLevel;
 
-        while let Some(free_region_index) = disk_map.find_first_free_region_index(1) {
-            disk_map.regions[free_region_index].id = region.id;
+impl Policy for BlockLevel {
+    fn name(&self) -> String {
+        String::from("block-level")
+    }
 
-            if disk_map.regions[free_region_index].size() < region.size() {
-                region.end -= disk_map.regions[free_region_index].size();
+    fn compact(&self, disk_map: &mut DiskMap) {
+        while let Some(mut region) = disk_map.regions.pop() {
+            if region.id.is_none() {
                 continue;
             }
 
-            if disk_map.regions[free_region_index].size() > region.size() {
-                disk_map.split_free_region(free_region_index, region.size());
+            let mut reordering_successful = false;
+
+            while let Some(free_region_index) = disk_map.find_first_free_region_index(1) {
+                disk_map.regions[free_region_index].id = region.id;
+
+                if disk_map.regions[free_region_index].size() < region.size() {
+                    region.end -= disk_map.regions[free_region_index].size();
+                    continue;
+                }
+
+                if disk_map.regions[free_region_index].size() > region.size() {
+                    disk_map.split_free_region(free_region_index, region.size());
+                }
+
+                reordering_successful = true;
+                break;
             }
 
-            reordering_successful = true;
-            break;
+            if !reordering_successful {
+                disk_map.regions.push(region);
+                break;
+            }
         }
 
-        if !reordering_successful {
-            disk_map.regions.push(region);
-            break;
-        }
+        disk_map.rebuild_free_regions();
     }
+}
 
-    disk_map.checksum()
+/// Which of the free spans left of a file, which the file fits into, it is moved to.
+enum Fit {
+    /// The leftmost span, as in part 2.
+    First,
+    /// The smallest span, the leftmost of them on ties.
+    Best,
+    /// The largest span, the leftmost of them on ties.
+    Worst,
 }
 
-fn part2(mut disk_map: DiskMap) -> usize {
-    disk_map.regions.clone().iter()
-        .rev()
-        .filter(|region| region.id.is_some())
-        .for_each(|region| {
-            let Some(free_region_index) = disk_map.find_first_free_region_index(region.size()) else { return; };
-
-            if disk_map.regions[free_region_index].start > region.start {
-                return;
-            }
+/// Moves whole files, starting with the highest id, into free spans left of them. A file stays in place if there is
+/// no such span it fits into.
+struct WholeFile {
+    fit: Fit,
+}
 
-            disk_map.regions[free_region_index].id = region.id;
+impl Policy for WholeFile {
+    fn name(&self) -> String {
+        let fit = match self.fit {
+            Fit::First => "first",
+            Fit::Best => "best",
+            Fit::Worst => "worst",
+        };
 
-            if disk_map.regions[free_region_index].size() > region.size() {
-                disk_map.split_free_region(free_region_index, region.size());
-            }
+        format!("{}-fit", fit)
+    }
 
-            disk_map.free_region(region);
-        });
+    /// Moves the files by descending id, which requires the disk map to be uncompacted: ordered by id from left to
+    /// right, so that every file is moved before the files to its left, as `FreeSpans` relies on.
+    fn compact(&self, disk_map: &mut DiskMap) {
+        let files = disk_map.regions.iter().filter(|region| region.id.is_some()).collect::<Vec<&DiskRegion>>();
+        assert!(
+            files.windows(2).all(|pair| pair[0].id < pair[1].id && pair[0].end < pair[1].start),
+            "whole-file compaction requires files ordered by id from left to right"
+        );
+
+        let mut free_spans = FreeSpans::new(&disk_map.regions);
+
+        disk_map.regions.iter_mut()
+            .rev()
+            .filter(|region| region.id.is_some())
+            .for_each(|region| {
+                let size = region.size();
+                let Some(start) = free_spans.take(size, region.start, &self.fit) else { return; };
+
+                region.start = start;
+                region.end = start + size - 1;
+            });
 
-    disk_map.checksum()
+        disk_map.rebuild_free_regions();
+    }
+}
+
+/// The starts of the free spans in a min-heap per span size. The leftmost span of a size is the head of its heap,
+/// so choosing among the sizes a file fits into and taking the span, including splitting it, is O(log n).
+/// The space a moved file leaves behind is never added, as only files to the left of it are moved afterwards, which
+/// holds as long as the files are moved from right to left.
+struct FreeSpans {
+    starts_per_size: Vec<BinaryHeap<Reverse<usize>>>,
+}
+
+impl FreeSpans {
+    fn new(regions: &[DiskRegion]) -> FreeSpans {
+        let free_regions = regions.iter().filter(|region| region.id.is_none());
+        let max_size = free_regions.clone().map(|region| region.size()).max().unwrap_or(0);
+
+        let mut starts_per_size = vec![BinaryHeap::new(); max_size + 1];
+        free_regions.for_each(|region| starts_per_size[region.size()].push(Reverse(region.start)));
+
+        FreeSpans { starts_per_size }
+    }
+
+    /// Takes `size` blocks from the start of the free span chosen by `fit` among the spans with at least that size
+    /// which start before `before`, and returns its start. The rest of the span remains free.
+    fn take(&mut self, size: usize, before: usize, fit: &Fit) -> Option<usize> {
+        // the leftmost span of each size, by ascending size
+        let mut candidates = (size..self.starts_per_size.len())
+            .filter_map(|span_size| self.starts_per_size[span_size].peek().map(|Reverse(start)| (*start, span_size)))
+            .filter(|(start, _)| *start < before);
+
+        let (start, span_size) = match fit {
+            Fit::First => candidates.min(),
+            Fit::Best => candidates.next(),
+            Fit::Worst => candidates.next_back(),
+        }?;
+
+        self.starts_per_size[span_size].pop();
+        if span_size > size {
+            self.starts_per_size[span_size - size].push(Reverse(start + size));
+        }
+
+        Some(start)
+    }
+}
+
+/// How scattered the files and the free space are.
+struct Fragmentation {
+    /// Files split into several regions.
+    fragmented_files: usize,
+    /// Free regions before the last file block, which could still be filled.
+    holes: usize,
+    hole_blocks: usize,
+    largest_hole: usize,
+}
+
+impl Display for Fragmentation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f, "{} fragmented files, {} holes with {} blocks (largest {})",
+            self.fragmented_files, self.holes, self.hole_blocks, self.largest_hole
+        )
+    }
 }
 
 #[derive(Clone)]
 struct DiskMap {
     regions: Vec<DiskRegion>,
+    /// The number of blocks, which does not change when compacting.
+    size: usize,
 }
 
 impl DiskMap {
@@ -77,13 +230,78 @@ impl DiskMap {
         self.regions.iter().map(|region| region.checksum()).sum()
     }
 
-    fn find_first_free_region_index(&self, min_size: usize) -> Option<usize> {
-        self.regions.iter().position(|region| region.id.is_none() && region.size() >= min_size)
+    /// Orders the regions by their start and recreates the free regions between them, after files have been moved.
+    /// Adjacent regions of the same file are merged.
+    fn rebuild_free_regions(&mut self) {
+        let mut file_regions = self.regions.iter()
+            .filter(|region| region.id.is_some())
+            .cloned()
+            .collect::<Vec<DiskRegion>>();
+        file_regions.sort_by_key(|region| region.start);
+
+        let mut regions: Vec<DiskRegion> = vec![];
+        let mut next_block_start_index = 0;
+
+        for region in file_regions {
+            if region.start > next_block_start_index {
+                regions.push(DiskRegion { id: None, start: next_block_start_index, end: region.start - 1 });
+            }
+
+            next_block_start_index = region.end + 1;
+
+            match regions.last_mut() {
+                Some(last_region) if last_region.id == region.id && last_region.end + 1 == region.start => last_region.end = region.end,
+                _ => regions.push(region),
+            }
+        }
+
+        if self.size > next_block_start_index {
+            regions.push(DiskRegion { id: None, start: next_block_start_index, end: self.size - 1 });
+        }
+
+        self.regions = regions;
+    }
+
+    /// Expects the regions to be ordered, e.g. after rebuilding the free regions.
+    fn fragmentation(&self) -> Fragmentation {
+        let mut region_counts = HashMap::new();
+        self.regions.iter()
+            .filter_map(|region| region.id)
+            .for_each(|id| *region_counts.entry(id).or_insert(0) += 1);
+
+        let last_file_index = self.regions.iter().rposition(|region| region.id.is_some()).unwrap_or(0);
+        let holes = self.regions[..last_file_index].iter()
+            .filter(|region| region.id.is_none())
+            .map(|region| region.size())
+            .collect::<Vec<usize>>();
+
+        Fragmentation {
+            fragmented_files: region_counts.values().filter(|count| **count > 1).count(),
+            holes: holes.len(),
+            hole_blocks: holes.iter().sum(),
+            largest_hole: holes.iter().max().copied().unwrap_or(0),
+        }
     }
 
-    fn free_region(&mut self, region: &DiskRegion) {
-        let Some(index) = self.regions.iter().rposition(|other_region| other_region.id == region.id) else { return; };
-        self.regions[index].id = None;
+    /// Renders the blocks in the puzzle's notation, e.g. `00...111...2`, with a `.` for every free block.
+    /// Returns `None` if there are files with ids of more than one digit, which the notation cannot show.
+    fn render_layout(&self) -> Option<String> {
+        let mut layout = String::with_capacity(self.size);
+
+        for region in &self.regions {
+            let block = match region.id {
+                None => '.',
+                Some(id) => char::from_digit(u32::try_from(id).ok()?, 10)?,
+            };
+
+            (0..region.size()).for_each(|_| layout.push(block));
+        }
+
+        Some(layout)
+    }
+
+    fn find_first_free_region_index(&self, min_size: usize) -> Option<usize> {
+        self.regions.iter().position(|region| region.id.is_none() && region.size() >= min_size)
     }
 
     fn split_free_region(&mut self, free_region_index: usize, first_half_size: usize) {
@@ -100,28 +318,28 @@ impl DiskMap {
 }
 
 impl FromStr for DiskMap {
-    type Err = ();
+    type Err = String;
 
+    /// Digits at even indices are files with the index halved as their id, digits at odd indices are free space.
+    /// Entries of size zero take up no blocks, but a file of size zero still takes its id. The free space around
+    /// such a file is merged into a single region.
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let mut regions: Vec<DiskRegion> = vec![];
-
         let mut next_block_start_index: usize = 0;
-        let mut next_file_id: usize = 0;
 
-        s.char_indices().for_each(|(index, c)| {
-            let size = c.to_digit(10).unwrap() as usize;
+        for (index, c) in s.trim().char_indices() {
+            let size = c.to_digit(10).ok_or_else(|| format!("index {}: invalid size '{}'", index, c))? as usize;
             if size == 0 {
-                return;
+                continue;
             }
 
-            let id = if index % 2 == 1 {
-                None
-            } else {
-                let file_id = next_file_id;
-                next_file_id += 1;
+            let id = if index % 2 == 0 { Some(index / 2) } else { None };
 
-                Some(file_id)
-            };
+            if let Some(last_region) = regions.last_mut().filter(|region| id.is_none() && region.id.is_none()) {
+                last_region.end += size;
+                next_block_start_index += size;
+                continue;
+            }
 
             regions.push(DiskRegion {
                 id,
@@ -130,9 +348,9 @@ impl FromStr for DiskMap {
             });
 
             next_block_start_index += size;
-        });
+        }
 
-        Ok(DiskMap { regions })
+        Ok(DiskMap { regions, size: next_block_start_index })
     }
 }
 
@@ -155,3 +373,128 @@ impl DiskRegion {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "2333133121414131402";
+
+    fn compact(disk_map: &DiskMap, policy: &dyn Policy) -> DiskMap {
+        let mut disk_map = disk_map.clone();
+        policy.compact(&mut disk_map);
+        disk_map
+    }
+
+    /// First-fit compaction by a linear scan over the free spans, which are found on the expanded blocks.
+    fn reference_first_fit_checksum(input: &str) -> usize {
+        let blocks = input.trim().chars()
+            .enumerate()
+            .flat_map(|(index, c)| {
+                let id = if index % 2 == 0 { Some(index / 2) } else { None };
+                std::iter::repeat_n(id, c.to_digit(10).unwrap() as usize)
+            })
+            .collect::<Vec<Option<usize>>>();
+
+        let mut free_spans: Vec<(usize, usize)> = vec![];
+        let mut files: Vec<(usize, usize, usize)> = vec![];
+
+        for (position, block) in blocks.iter().enumerate() {
+            match (block, free_spans.last_mut(), files.last_mut()) {
+                (None, Some((start, size)), _) if *start + *size == position => *size += 1,
+                (None, _, _) => free_spans.push((position, 1)),
+                (Some(id), _, Some((file_id, _, size))) if file_id == id => *size += 1,
+                (Some(id), _, _) => files.push((*id, position, 1)),
+            }
+        }
+
+        files.iter()
+            .rev()
+            .map(|(id, start, size)| {
+                let new_start = match free_spans.iter_mut().find(|(span_start, span_size)| *span_start < *start && *span_size >= *size) {
+                    Some((span_start, span_size)) => {
+                        let new_start = *span_start;
+                        *span_start += size;
+                        *span_size -= size;
+                        new_start
+                    }
+                    None => *start,
+                };
+
+                (new_start..new_start + size).sum::<usize>() * id
+            })
+            .sum()
+    }
+
+    /// Generates a disk map of random digits, including zero-size files and free space.
+    fn generate_disk_map(length: usize, seed: u64) -> String {
+        let mut state = seed;
+
+        (0..length)
+            .map(|_| {
+                state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+                char::from_digit(((state >> 33) % 10) as u32, 10).unwrap()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn first_fit_matches_reference() {
+        let mut inputs = vec![String::from(EXAMPLE), String::from("120355"), String::from(include_str!("input.txt"))];
+        inputs.extend((1..=20).map(|seed| generate_disk_map(1 + seed as usize * 7, seed)));
+        inputs.push(generate_disk_map(20_001, 42));
+
+        for input in inputs {
+            let mut disk_map = DiskMap::from_str(&input).unwrap();
+            WholeFile { fit: Fit::First }.compact(&mut disk_map);
+
+            assert_eq!(disk_map.checksum(), reference_first_fit_checksum(&input), "disk map {}", input);
+        }
+    }
+
+    #[test]
+    fn zero_length_file_takes_its_id() {
+        let disk_map = DiskMap::from_str("120355").unwrap();
+
+        assert_eq!(disk_map.regions.iter().map(|region| region.id).collect::<Vec<Option<usize>>>(), vec![Some(0), None, Some(2), None]);
+        assert_eq!(disk_map.render_layout().unwrap(), "0.....22222.....");
+    }
+
+    #[test]
+    fn whole_files_move_into_free_space_around_zero_length_file() {
+        let disk_map = DiskMap::from_str("120355").unwrap();
+
+        for fit in [Fit::First, Fit::Best, Fit::Worst] {
+            let compacted = compact(&disk_map, &WholeFile { fit });
+
+            assert_eq!(compacted.render_layout().unwrap(), "022222..........");
+            assert_eq!(compacted.checksum(), 30);
+        }
+
+        assert_eq!(compact(&disk_map, &BlockLevel).checksum(), 30);
+    }
+
+    #[test]
+    #[should_panic(expected = "requires files ordered by id")]
+    fn whole_file_compaction_rejects_compacted_disk_maps() {
+        let disk_map = compact(&DiskMap::from_str(EXAMPLE).unwrap(), &WholeFile { fit: Fit::First });
+
+        compact(&disk_map, &WholeFile { fit: Fit::First });
+    }
+
+    #[test]
+    fn ignores_surrounding_whitespace() {
+        for input in [EXAMPLE, "2333133121414131402\n", "2333133121414131402\r\n", " 2333133121414131402 \n\n"] {
+            let disk_map = DiskMap::from_str(input).unwrap();
+
+            assert_eq!(part1(disk_map.clone()), 1928);
+            assert_eq!(part2(disk_map), 2858);
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_characters() {
+        assert_eq!(DiskMap::from_str("12a4").err(), Some(String::from("index 2: invalid size 'a'")));
+        assert_eq!(DiskMap::from_str("12\n34").err(), Some(String::from("index 2: invalid size '\n'")));
+    }
+}
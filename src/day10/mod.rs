@@ -1,14 +1,20 @@
 use std::collections::{HashMap, HashSet, VecDeque};
 use std::str::FromStr;
 
+use crate::runner::{self, Run};
+
 #[allow(dead_code)]
 pub fn main() {
     let input = include_str!("input.txt");
 
-    let map = Map::from_str(input).unwrap();
+    runner::run(input, solve).print();
+}
+
+pub fn solve(input: &str, run: &mut Run) {
+    let map = run.parse(|| Map::from_str(input).unwrap());
 
-    println!("PART 1: {}", part1(&map));
-    println!("PART 2: {}", part2(&map));
+    run.part(|| part1(&map));
+    run.part(|| part2(&map));
 }
 
 fn part1(map: &Map) -> usize {
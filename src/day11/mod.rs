@@ -1,14 +1,21 @@
 use std::collections::{HashMap};
 use std::str::FromStr;
 
+use crate::checked;
+use crate::runner::{self, Run};
+
 #[allow(dead_code)]
 pub fn main() {
     let input = include_str!("input.txt");
 
-    let stone_arrangement = StoneArrangement::from_str(input).unwrap();
+    runner::run(input, solve).print();
+}
+
+pub fn solve(input: &str, run: &mut Run) {
+    let stone_arrangement = run.parse(|| StoneArrangement::from_str(input).unwrap());
 
-    println!("PART 1: {}", part1(&stone_arrangement));
-    println!("PART 2: {}", part2(&stone_arrangement));
+    run.part(|| part1(&stone_arrangement));
+    run.part(|| part2(&stone_arrangement));
 }
 
 fn part1(stone_arrangement: &StoneArrangement) -> usize {
@@ -39,7 +46,8 @@ impl StoneArrangement {
         self.stones = self.stones.iter()
             .fold(HashMap::new(), |mut stones, (stone, amount)| {
                 stone.transformed().into_iter().for_each(|new_stone| {
-                    *stones.entry(new_stone).or_insert(0) += amount;
+                    let count = stones.entry(new_stone).or_insert(0);
+                    *count = checked::add(*count, *amount, "day11 stone count");
                 });
 
                 stones
@@ -47,7 +55,7 @@ impl StoneArrangement {
     }
 
     fn stone_count(&self) -> usize {
-        self.stones.values().sum()
+        checked::sum(self.stones.values().copied(), "day11 total stone count")
     }
 }
 
@@ -84,7 +92,7 @@ impl Stone {
             ];
         }
 
-        vec![Stone::from((self.as_number() * 2024).to_string())]
+        vec![Stone::from(checked::mul(self.as_number(), 2024, "day11 stone number").to_string())]
     }
 
     fn as_number(&self) -> u64 {
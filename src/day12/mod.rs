@@ -1,14 +1,20 @@
 use std::collections::{HashMap, HashSet, VecDeque};
 use std::str::FromStr;
 
+use crate::runner::{self, Run};
+
 #[allow(dead_code)]
 pub fn main() {
     let input = include_str!("input.txt");
 
-    let garden = Garden::from_str(input).unwrap();
+    runner::run(input, solve).print();
+}
+
+pub fn solve(input: &str, run: &mut Run) {
+    let garden = run.parse(|| Garden::from_str(input).unwrap());
 
-    println!("PART 1: {}", part1(&garden));
-    println!("PART 2: {}", part2(&garden));
+    run.part(|| part1(&garden));
+    run.part(|| part2(&garden));
 }
 
 fn part1(garden: &Garden) -> usize {
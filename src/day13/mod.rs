@@ -1,28 +1,37 @@
 use std::str::FromStr;
 use regex::Regex;
 
+use crate::checked;
+use crate::runner::{self, Run};
+
 #[allow(dead_code)]
 pub fn main() {
     let input = include_str!("input.txt");
 
-    let claw_machines = input.split("\n\n")
+    runner::run(input, solve).print();
+}
+
+pub fn solve(input: &str, run: &mut Run) {
+    let claw_machines = run.parse(|| input.split("\n\n")
         .map(|claw_machine| ClawMachine::from_str(claw_machine).unwrap())
-        .collect::<Vec<ClawMachine>>();
+        .collect::<Vec<ClawMachine>>());
 
-    println!("PART 1: {}", part1(&claw_machines));
-    println!("PART 2: {}", part2(&claw_machines));
+    run.part(|| part1(&claw_machines));
+    run.part(|| part2(&claw_machines));
 }
 
 fn part1(claw_machines: &Vec<ClawMachine>) -> i64 {
-    claw_machines.iter()
-        .map(|claw_machine| claw_machine.cost())
-        .sum()
+    let costs = claw_machines.iter()
+        .map(|claw_machine| claw_machine.cost());
+
+    checked::sum(costs, "day13 total cost")
 }
 
 fn part2(claw_machines: &Vec<ClawMachine>) -> i64 {
-    claw_machines.iter()
-        .map(|claw_machine| claw_machine.with_price_increase(10000000000000).cost())
-        .sum()
+    let costs = claw_machines.iter()
+        .map(|claw_machine| claw_machine.with_price_increase(10000000000000).cost());
+
+    checked::sum(costs, "day13 total cost")
 }
 
 struct ClawMachine {
@@ -33,28 +42,40 @@ struct ClawMachine {
 
 impl ClawMachine {
     fn cost(&self) -> i64 {
-        let button_b_amount = (self.price.1 * self.button_a.0 - self.button_a.1 * self.price.0) /
-            (self.button_b.1 * self.button_a.0 - self.button_b.0 * self.button_a.1);
+        const OPERATION: &str = "day13 button amounts";
+        let (mul, sub, div) = (checked::mul::<i64>, checked::sub::<i64>, checked::div::<i64>);
+
+        let button_b_amount = div(
+            sub(mul(self.price.1, self.button_a.0, OPERATION), mul(self.button_a.1, self.price.0, OPERATION), OPERATION),
+            sub(mul(self.button_b.1, self.button_a.0, OPERATION), mul(self.button_b.0, self.button_a.1, OPERATION), OPERATION),
+            OPERATION,
+        );
 
-        let button_a_amount = (self.price.0 - button_b_amount * self.button_b.0) / self.button_a.0;
+        let button_a_amount = div(sub(self.price.0, mul(button_b_amount, self.button_b.0, OPERATION), OPERATION), self.button_a.0, OPERATION);
 
         if self.is_valid_result(button_a_amount, button_b_amount) {
-            button_a_amount * 3 + button_b_amount
+            checked::add(checked::mul(button_a_amount, 3, "day13 cost"), button_b_amount, "day13 cost")
         } else {
             0
         }
     }
 
     fn is_valid_result(&self, button_a_amount: i64, button_b_amount: i64) -> bool {
-        (self.button_a.0 * button_a_amount + self.button_b.0 * button_b_amount == self.price.0) &&
-            (self.button_a.1 * button_a_amount + self.button_b.1 * button_b_amount == self.price.1)
+        const OPERATION: &str = "day13 result validation";
+        let (add, mul) = (checked::add::<i64>, checked::mul::<i64>);
+
+        (add(mul(self.button_a.0, button_a_amount, OPERATION), mul(self.button_b.0, button_b_amount, OPERATION), OPERATION) == self.price.0) &&
+            (add(mul(self.button_a.1, button_a_amount, OPERATION), mul(self.button_b.1, button_b_amount, OPERATION), OPERATION) == self.price.1)
     }
 
     fn with_price_increase(&self, increase: i64) -> ClawMachine {
         ClawMachine {
             button_a: self.button_a,
             button_b: self.button_b,
-            price: (self.price.0 + increase, self.price.1 + increase),
+            price: (
+                checked::add(self.price.0, increase, "day13 price increase"),
+                checked::add(self.price.1, increase, "day13 price increase"),
+            ),
         }
     }
 }
@@ -2,14 +2,20 @@ use std::collections::HashMap;
 use std::str::FromStr;
 use regex::Regex;
 
+use crate::runner::{self, Run};
+
 #[allow(dead_code)]
 pub fn main() {
     let input = include_str!("input.txt");
 
-    let robots = input.lines().map(|line| Robot::from_str(line).unwrap()).collect::<Vec<Robot>>();
+    runner::run(input, solve).print();
+}
+
+pub fn solve(input: &str, run: &mut Run) {
+    let robots = run.parse(|| input.lines().map(|line| Robot::from_str(line).unwrap()).collect::<Vec<Robot>>());
 
-    println!("PART 1: {}", part1(&robots));
-    println!("PART 2: {}", part2(&robots));
+    run.part(|| part1(&robots));
+    run.part(|| part2(&robots));
 }
 
 fn part1(robots: &Vec<Robot>) -> i32 {
@@ -1,15 +1,21 @@
 use std::collections::{HashSet};
 use std::str::FromStr;
 
+use crate::runner::{self, Run};
+
 #[allow(dead_code)]
 pub fn main() {
     let input = include_str!("input.txt");
 
+    runner::run(input, solve).print();
+}
+
+pub fn solve(input: &str, run: &mut Run) {
     let [raw_warehouse, raw_moves] = input.split("\n\n").collect::<Vec<&str>>()[..] else { return; };
-    let moves = raw_moves.chars().map(|c| MoveDirection::from(c)).collect::<Vec<MoveDirection>>();
+    let moves = run.parse(|| raw_moves.chars().map(|c| MoveDirection::from(c)).collect::<Vec<MoveDirection>>());
 
-    println!("PART 1: {}", part1(raw_warehouse, &moves));
-    println!("PART 2: {}", part2(raw_warehouse, &moves));
+    run.part(|| part1(raw_warehouse, &moves));
+    run.part(|| part2(raw_warehouse, &moves));
 }
 
 fn part1(raw_warehouse: &str, moves: &Vec<MoveDirection>) -> i32 {
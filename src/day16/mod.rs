@@ -2,14 +2,20 @@ use std::cmp::Ordering;
 use std::collections::{BinaryHeap, HashMap, HashSet, VecDeque};
 use std::str::FromStr;
 
+use crate::runner::{self, Run};
+
 #[allow(dead_code)]
 pub fn main() {
     let input = include_str!("input.txt");
 
-    let maze = Maze::from_str(input).unwrap();
+    runner::run(input, solve).print();
+}
+
+pub fn solve(input: &str, run: &mut Run) {
+    let maze = run.parse(|| Maze::from_str(input).unwrap());
 
-    println!("PART 1: {}", part1(&maze));
-    println!("PART 2: {}", part2(&maze));
+    run.part(|| part1(&maze));
+    run.part(|| part2(&maze));
 }
 
 fn part1(maze: &Maze) -> u64 {
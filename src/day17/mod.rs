@@ -1,13 +1,19 @@
 use std::str::FromStr;
 
+use crate::runner::{self, Run};
+
 #[allow(dead_code)]
 pub fn main() {
     let input = include_str!("input.txt");
 
-    let computer = Computer::from_str(input).unwrap();
+    runner::run(input, solve).print();
+}
+
+pub fn solve(input: &str, run: &mut Run) {
+    let computer = run.parse(|| Computer::from_str(input).unwrap());
 
-    println!("PART 1: {}", part1(computer.clone()));
-    println!("PART 2: {}", part2(&computer));
+    run.part(|| part1(computer.clone()));
+    run.part(|| part2(&computer));
 }
 
 fn part1(mut computer: Computer) -> String {
@@ -2,14 +2,20 @@ use std::cmp::Ordering;
 use std::collections::{BinaryHeap, HashMap, HashSet};
 use std::str::FromStr;
 
+use crate::runner::{self, Run};
+
 #[allow(dead_code)]
 pub fn main() {
     let input = include_str!("input.txt");
 
-    let memory_space = MemorySpace::from_str(input).unwrap();
+    runner::run(input, solve).print();
+}
+
+pub fn solve(input: &str, run: &mut Run) {
+    let memory_space = run.parse(|| MemorySpace::from_str(input).unwrap());
 
-    println!("PART 1: {}", part1(&memory_space));
-    println!("PART 2: {}", part2(&memory_space));
+    run.part(|| part1(&memory_space));
+    run.part(|| part2(&memory_space));
 }
 
 fn part1(memory_space: &MemorySpace) -> u64 {
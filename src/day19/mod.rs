@@ -1,16 +1,25 @@
 use std::collections::{HashMap};
 use regex::Regex;
 
+use crate::runner::{self, Run};
+
 #[allow(dead_code)]
 pub fn main() {
     let input = include_str!("input.txt");
 
+    runner::run(input, solve).print();
+}
+
+pub fn solve(input: &str, run: &mut Run) {
     let [raw_available_towels, raw_designs] = input.split("\n\n").collect::<Vec<&str>>()[..] else { return; };
-    let available_towels = raw_available_towels.split(", ").map(|towel| towel.to_string()).collect::<Vec<String>>();
-    let designs = raw_designs.split("\n").map(|design| design.to_string()).collect::<Vec<String>>();
 
-    println!("PART 1: {}", part1(&available_towels, &designs));
-    println!("PART 2: {}", part2(&available_towels, &designs));
+    let (available_towels, designs) = run.parse(|| (
+        raw_available_towels.split(", ").map(|towel| towel.to_string()).collect::<Vec<String>>(),
+        raw_designs.split("\n").map(|design| design.to_string()).collect::<Vec<String>>(),
+    ));
+
+    run.part(|| part1(&available_towels, &designs));
+    run.part(|| part2(&available_towels, &designs));
 }
 
 fn part1(available_towels: &Vec<String>, designs: &Vec<String>) -> usize {
@@ -1,13 +1,19 @@
 use std::collections::{HashMap, HashSet};
 
+use crate::runner::{self, Run};
+
 #[allow(dead_code)]
 pub fn main() {
     let input = include_str!("input.txt");
 
-    let codes = input.lines().map(|line| line.to_string()).collect::<Vec<String>>();
+    runner::run(input, solve).print();
+}
+
+pub fn solve(input: &str, run: &mut Run) {
+    let codes = run.parse(|| input.lines().map(|line| line.to_string()).collect::<Vec<String>>());
 
-    println!("PART 1: {}", part1(&codes));
-    println!("PART 2: {}", part2(&codes));
+    run.part(|| part1(&codes));
+    run.part(|| part2(&codes));
 }
 
 fn part1(codes: &Vec<String>) -> usize {
@@ -1,19 +1,27 @@
 use std::collections::{HashMap, HashSet};
 
+use crate::checked;
+use crate::runner::{self, Run};
+
 #[allow(dead_code)]
 pub fn main() {
     let input = include_str!("input.txt");
 
-    let secret_numbers = input.lines().map(|line| line.parse().unwrap()).collect::<Vec<i64>>();
+    runner::run(input, solve).print();
+}
+
+pub fn solve(input: &str, run: &mut Run) {
+    let secret_numbers = run.parse(|| input.lines().map(|line| line.parse().unwrap()).collect::<Vec<i64>>());
 
-    println!("PART 1: {}", part1(&secret_numbers));
-    println!("PART 2: {}", part2(&secret_numbers));
+    run.part(|| part1(&secret_numbers));
+    run.part(|| part2(&secret_numbers));
 }
 
 fn part1(secret_numbers: &Vec<i64>) -> i64 {
-    secret_numbers.iter()
-        .map(|secret_number| *generate_n_secret_numbers(*secret_number, 2000).last().unwrap())
-        .sum()
+    let secret_numbers = secret_numbers.iter()
+        .map(|secret_number| *generate_n_secret_numbers(*secret_number, 2000).last().unwrap());
+
+    checked::sum(secret_numbers, "day22 sum of secret numbers")
 }
 
 fn part2(secret_numbers: &Vec<i64>) -> i64 {
@@ -62,13 +70,13 @@ fn generate_n_secret_numbers(mut secret_number: i64, n: usize) -> Vec<i64> {
 }
 
 fn generate_next_number(mut secret_number: i64) -> i64 {
-    secret_number ^= secret_number * 64;
+    secret_number ^= checked::mul(secret_number, 64, "day22 secret multiplication");
     secret_number %= 16777216;
 
     secret_number ^= secret_number / 32;
     secret_number %= 16777216;
 
-    secret_number ^= secret_number * 2048;
+    secret_number ^= checked::mul(secret_number, 2048, "day22 secret multiplication");
     secret_number %= 16777216;
 
     secret_number
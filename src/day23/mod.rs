@@ -1,14 +1,20 @@
 use std::collections::{HashSet};
 use std::str::FromStr;
 
+use crate::runner::{self, Run};
+
 #[allow(dead_code)]
 pub fn main() {
     let input = include_str!("input.txt");
 
-    let graph = Graph::from_str(input).unwrap();
+    runner::run(input, solve).print();
+}
+
+pub fn solve(input: &str, run: &mut Run) {
+    let graph = run.parse(|| Graph::from_str(input).unwrap());
 
-    println!("PART 1: {}", part1(&graph));
-    println!("PART 2: {}", part2(&graph));
+    run.part(|| part1(&graph));
+    run.part(|| part2(&graph));
 }
 
 fn part1(graph: &Graph) -> usize {
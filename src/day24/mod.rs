@@ -2,14 +2,20 @@ use std::collections::{HashMap};
 use std::str::FromStr;
 use regex::Regex;
 
+use crate::runner::{self, Run};
+
 #[allow(dead_code)]
 pub fn main() {
     let input = include_str!("input.txt");
 
-    let circuit = Circuit::from_str(input).unwrap();
+    runner::run(input, solve).print();
+}
+
+pub fn solve(input: &str, run: &mut Run) {
+    let circuit = run.parse(|| Circuit::from_str(input).unwrap());
 
-    println!("PART 1: {}", part1(&circuit));
-    println!("PART 2: {}", part2());
+    run.part(|| part1(&circuit));
+    run.part(part2);
 }
 
 fn part1(circuit: &Circuit) -> u64 {
@@ -1,10 +1,16 @@
 use std::str::FromStr;
 
+use crate::runner::{self, Run};
+
 #[allow(dead_code)]
 pub fn main() {
     let input = include_str!("input.txt");
 
-    println!("PART 1: {}", part1(input));
+    runner::run(input, solve).print();
+}
+
+pub fn solve(input: &str, run: &mut Run) {
+    run.part(|| part1(input));
 }
 
 fn part1(input: &str) -> usize {
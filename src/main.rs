@@ -1,5 +1,6 @@
 #![feature(iter_map_windows)]
 
+mod checked;
 mod day01;
 mod day02;
 mod day03;
@@ -25,8 +26,15 @@ mod day22;
 mod day23;
 mod day24;
 mod day25;
+mod runner;
 
 fn main() {
+    let args = std::env::args().skip(1).collect::<Vec<String>>();
+    if !args.is_empty() {
+        runner::main(&args);
+        return;
+    }
+
     // day01::main();
     // day02::main();
     // day03::main();
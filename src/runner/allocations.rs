@@ -0,0 +1,142 @@
+use std::fmt::{Display, Formatter};
+
+/// Heap usage of a measured section. Peak heap is relative to the heap size at the start of the section.
+#[derive(Clone, Copy, Default)]
+pub struct Allocations {
+    pub peak_bytes: usize,
+    pub count: usize,
+    pub allocated_bytes: usize,
+}
+
+impl Allocations {
+    pub fn combine(self, other: Allocations) -> Allocations {
+        Allocations {
+            peak_bytes: self.peak_bytes.max(other.peak_bytes),
+            count: self.count + other.count,
+            allocated_bytes: self.allocated_bytes + other.allocated_bytes,
+        }
+    }
+
+    pub fn serialize(&self) -> String {
+        format!("{},{},{}", self.peak_bytes, self.count, self.allocated_bytes)
+    }
+
+    pub fn deserialize(s: &str) -> Option<Allocations> {
+        let [peak_bytes, count, allocated_bytes] = s.split(',').collect::<Vec<&str>>()[..] else { return None; };
+
+        Some(Allocations {
+            peak_bytes: peak_bytes.parse().ok()?,
+            count: count.parse().ok()?,
+            allocated_bytes: allocated_bytes.parse().ok()?,
+        })
+    }
+}
+
+impl Display for Allocations {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "peak {}, {} allocations, {} allocated", format_bytes(self.peak_bytes), self.count, format_bytes(self.allocated_bytes))
+    }
+}
+
+fn format_bytes(bytes: usize) -> String {
+    match bytes {
+        0..1024 => format!("{} B", bytes),
+        1024..1_048_576 => format!("{:.1} KiB", bytes as f64 / 1024.0),
+        1_048_576..1_073_741_824 => format!("{:.1} MiB", bytes as f64 / 1_048_576.0),
+        _ => format!("{:.2} GiB", bytes as f64 / 1_073_741_824.0),
+    }
+}
+
+#[cfg(feature = "alloc-stats")]
+pub use counting::measure;
+
+/// Allocations are only counted with the `alloc-stats` feature enabled.
+#[cfg(not(feature = "alloc-stats"))]
+pub fn measure<T>(f: impl FnOnce() -> T) -> (T, Option<Allocations>) {
+    (f(), None)
+}
+
+#[cfg(feature = "alloc-stats")]
+mod counting {
+    use std::alloc::{GlobalAlloc, Layout, System};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::Allocations;
+
+    #[global_allocator]
+    static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+    static CURRENT_BYTES: AtomicUsize = AtomicUsize::new(0);
+    static PEAK_BYTES: AtomicUsize = AtomicUsize::new(0);
+    static COUNT: AtomicUsize = AtomicUsize::new(0);
+    static ALLOCATED_BYTES: AtomicUsize = AtomicUsize::new(0);
+
+    struct CountingAllocator;
+
+    impl CountingAllocator {
+        fn record_allocation(size: usize) {
+            let current = CURRENT_BYTES.fetch_add(size, Ordering::Relaxed) + size;
+            PEAK_BYTES.fetch_max(current, Ordering::Relaxed);
+            COUNT.fetch_add(1, Ordering::Relaxed);
+            ALLOCATED_BYTES.fetch_add(size, Ordering::Relaxed);
+        }
+
+        fn record_deallocation(size: usize) {
+            CURRENT_BYTES.fetch_sub(size, Ordering::Relaxed);
+        }
+    }
+
+    unsafe impl GlobalAlloc for CountingAllocator {
+        unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+            let pointer = unsafe { System.alloc(layout) };
+            if !pointer.is_null() {
+                Self::record_allocation(layout.size());
+            }
+
+            pointer
+        }
+
+        unsafe fn dealloc(&self, pointer: *mut u8, layout: Layout) {
+            unsafe { System.dealloc(pointer, layout) };
+            Self::record_deallocation(layout.size());
+        }
+
+        unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+            let pointer = unsafe { System.alloc_zeroed(layout) };
+            if !pointer.is_null() {
+                Self::record_allocation(layout.size());
+            }
+
+            pointer
+        }
+
+        unsafe fn realloc(&self, pointer: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+            let new_pointer = unsafe { System.realloc(pointer, layout, new_size) };
+            if !new_pointer.is_null() {
+                Self::record_deallocation(layout.size());
+                Self::record_allocation(new_size);
+            }
+
+            new_pointer
+        }
+    }
+
+    /// Runs `f` and returns its heap usage. The counters are global, so allocations of other threads
+    /// running at the same time are included.
+    pub fn measure<T>(f: impl FnOnce() -> T) -> (T, Option<Allocations>) {
+        let start_bytes = CURRENT_BYTES.load(Ordering::Relaxed);
+        let start_count = COUNT.load(Ordering::Relaxed);
+        let start_allocated_bytes = ALLOCATED_BYTES.load(Ordering::Relaxed);
+        PEAK_BYTES.store(start_bytes, Ordering::Relaxed);
+
+        let result = f();
+
+        let allocations = Allocations {
+            peak_bytes: PEAK_BYTES.load(Ordering::Relaxed).saturating_sub(start_bytes),
+            count: COUNT.load(Ordering::Relaxed) - start_count,
+            allocated_bytes: ALLOCATED_BYTES.load(Ordering::Relaxed) - start_allocated_bytes,
+        };
+
+        (result, Some(allocations))
+    }
+}
@@ -0,0 +1,71 @@
+use std::fs;
+use std::path::PathBuf;
+use std::process;
+
+use super::isolation::Outcome;
+use super::{parse_days, Inputs, Options};
+
+pub fn main(options: &Options) -> Result<(), String> {
+    let root = options.get("inputs-root").ok_or("missing --inputs-root")?;
+    let isolation = options.isolation()?;
+    let timeout = options.timeout()?;
+
+    let mut users = fs::read_dir(root)
+        .map_err(|error| format!("failed to read {}: {}", root, error))?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.is_dir())
+        .collect::<Vec<PathBuf>>();
+    users.sort();
+
+    let mut flags = vec![];
+
+    for user_directory in users {
+        let user = user_directory.file_name().unwrap_or_default().to_string_lossy().to_string();
+        let inputs = Inputs::new(&user_directory);
+
+        println!("USER {}", user);
+
+        for day in parse_days("all")? {
+            let Some(input) = inputs.input(day) else { continue; };
+
+            let run = match isolation.run(day, &input, timeout) {
+                Outcome::Solved(run) => run,
+                Outcome::Failed(failure) => {
+                    println!("  DAY {:02}: {}", day, failure);
+                    flags.push(format!("{} day{:02}: {}", user, day, failure));
+                    continue;
+                }
+            };
+
+            let answers = inputs.answers(day);
+
+            let parts = (0..run.parts.len())
+                .map(|part| {
+                    let answer = run.answer(part).unwrap_or_default();
+
+                    match (answers.is_correct(&run, part), answers.get(part)) {
+                        (Some(false), Some(expected)) => {
+                            flags.push(format!("{} day{:02} part {}: expected {}, got {}", user, day, part + 1, expected, answer));
+                            format!("{} ✗", answer)
+                        }
+                        (Some(true), _) => format!("{} ✓", answer),
+                        _ => format!("{} ?", answer),
+                    }
+                })
+                .collect::<Vec<String>>();
+
+            println!("  DAY {:02}: {}", day, parts.join(" | "));
+        }
+    }
+
+    if flags.is_empty() {
+        println!("all days solved and matching the stored answers");
+        return Ok(());
+    }
+
+    println!("FLAGGED");
+    flags.iter().for_each(|flag| println!("  {}", flag));
+
+    process::exit(1);
+}
@@ -0,0 +1,193 @@
+use std::any::Any;
+use std::fmt::{Display, Formatter};
+use std::io::{self, Read, Write};
+use std::panic::{self, AssertUnwindSafe};
+use std::process::{Command, Stdio};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use super::allocations::Allocations;
+use super::{parse_days, run, Options, Part, Run, DAYS};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+pub enum Outcome {
+    Solved(Run),
+    Failed(Failure),
+}
+
+pub enum Failure {
+    Panicked(String),
+    TimedOut(Duration),
+}
+
+impl Display for Failure {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Failure::Panicked(message) => write!(f, "panicked: {}", message),
+            Failure::TimedOut(timeout) => write!(f, "timed out after {:?}", timeout),
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+pub enum Isolation {
+    /// Runs the solver on the current thread without any protection.
+    None,
+    /// Runs the solver on a worker thread and catches its panics. A solver that exceeds the timeout is left
+    /// running in the background, as threads can't be killed. Panics can only be caught if the binary is
+    /// built without `panic = "abort"`, e.g. in the dev profile.
+    Thread,
+    /// Runs the solver in a child process of this binary, which is killed once it exceeds the timeout.
+    Process,
+}
+
+impl Isolation {
+    pub fn from_name(name: &str) -> Result<Isolation, String> {
+        match name {
+            "none" => Ok(Isolation::None),
+            "thread" => Ok(Isolation::Thread),
+            "process" => Ok(Isolation::Process),
+            _ => Err(format!("unknown isolation: {}", name)),
+        }
+    }
+
+    /// Worker threads can't survive a panic if the profile aborts on panic, so child processes are used instead.
+    pub fn default_for_profile() -> Isolation {
+        if cfg!(panic = "abort") { Isolation::Process } else { Isolation::Thread }
+    }
+
+    pub fn run(&self, day: usize, input: &str, timeout: Duration) -> Outcome {
+        match self {
+            Isolation::None => Outcome::Solved(run(input, DAYS[day - 1])),
+            Isolation::Thread => run_on_thread(day, input, timeout),
+            Isolation::Process => run_in_process(day, input, timeout).unwrap_or_else(|error| {
+                Outcome::Failed(Failure::Panicked(format!("failed to run child process: {}", error)))
+            }),
+        }
+    }
+}
+
+fn run_on_thread(day: usize, input: &str, timeout: Duration) -> Outcome {
+    let input = input.to_string();
+    let (sender, receiver) = mpsc::channel();
+
+    thread::spawn(move || {
+        let result = panic::catch_unwind(AssertUnwindSafe(|| run(&input, DAYS[day - 1])));
+        let _ = sender.send(result);
+    });
+
+    match receiver.recv_timeout(timeout) {
+        Ok(Ok(run)) => Outcome::Solved(run),
+        Ok(Err(payload)) => Outcome::Failed(Failure::Panicked(panic_message(payload))),
+        Err(_) => Outcome::Failed(Failure::TimedOut(timeout)),
+    }
+}
+
+fn panic_message(payload: Box<dyn Any + Send>) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        return message.to_string();
+    }
+
+    payload.downcast_ref::<String>().cloned().unwrap_or_else(|| String::from("unknown panic"))
+}
+
+/// Spawns `solve <day>`, passing the input via stdin and reading the results in the format written by [`child_main`].
+fn run_in_process(day: usize, input: &str, timeout: Duration) -> io::Result<Outcome> {
+    let mut child = Command::new(std::env::current_exe()?)
+        .args(["solve", &day.to_string()])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+
+    let mut stdin = child.stdin.take().unwrap();
+    let input = input.to_string();
+    thread::spawn(move || stdin.write_all(input.as_bytes()));
+
+    let stdout = read_in_background(child.stdout.take().unwrap());
+    let stderr = read_in_background(child.stderr.take().unwrap());
+
+    let start = Instant::now();
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+
+        if start.elapsed() > timeout {
+            child.kill()?;
+            child.wait()?;
+            return Ok(Outcome::Failed(Failure::TimedOut(timeout)));
+        }
+
+        thread::sleep(POLL_INTERVAL);
+    };
+
+    let stdout = stdout.join().unwrap_or_default();
+    let stderr = stderr.join().unwrap_or_default();
+
+    if !status.success() {
+        let message = stderr.lines()
+            .skip_while(|line| !line.contains("panicked at"))
+            .nth(1)
+            .map(|line| line.to_string())
+            .unwrap_or_else(|| format!("child process exited with {}", status));
+
+        return Ok(Outcome::Failed(Failure::Panicked(message)));
+    }
+
+    Ok(Outcome::Solved(parse_child_output(&stdout)))
+}
+
+fn read_in_background(mut reader: impl Read + Send + 'static) -> thread::JoinHandle<String> {
+    thread::spawn(move || {
+        let mut output = String::new();
+        let _ = reader.read_to_string(&mut output);
+        output
+    })
+}
+
+fn parse_child_output(output: &str) -> Run {
+    output.lines().fold(Run::default(), |mut run, line| {
+        let mut fields = line.splitn(4, '\t');
+
+        let kind = fields.next();
+        let duration = fields.next().and_then(|nanos| nanos.parse().ok()).map(Duration::from_nanos);
+        let allocations = fields.next().and_then(Allocations::deserialize);
+
+        match (kind, duration) {
+            (Some("parse"), Some(duration)) => {
+                run.parse_duration = duration;
+                run.parse_allocations = allocations;
+            }
+            (Some("part"), Some(duration)) => run.parts.push(Part {
+                answer: fields.next().unwrap_or_default().to_string(),
+                duration,
+                allocations,
+            }),
+            _ => {}
+        }
+
+        run
+    })
+}
+
+/// Entry point of the child process: solves a single day on the input from stdin and writes one line per
+/// measurement to stdout, `parse <nanos> <allocations>` and `part <nanos> <allocations> <answer>`, separated by
+/// tabs. The allocations are `-` if they aren't counted.
+pub fn child_main(options: &Options) -> Result<(), String> {
+    let [day] = parse_days(options.positional(0).unwrap_or_default())?[..] else { return Err(String::from("solve expects a single day")); };
+
+    let mut input = String::new();
+    io::stdin().read_to_string(&mut input).map_err(|error| format!("failed to read input: {}", error))?;
+
+    let run = run(&input, DAYS[day - 1]);
+
+    let serialize = |allocations: Option<Allocations>| allocations.map(|allocations| allocations.serialize()).unwrap_or(String::from("-"));
+
+    println!("parse\t{}\t{}", run.parse_duration.as_nanos(), serialize(run.parse_allocations));
+    run.parts.iter().for_each(|part| println!("part\t{}\t{}\t{}", part.duration.as_nanos(), serialize(part.allocations), part.answer));
+
+    Ok(())
+}
@@ -0,0 +1,311 @@
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::time::{Duration, Instant};
+
+use allocations::{measure, Allocations};
+use isolation::{Isolation, Outcome};
+
+use crate::{
+    day01, day02, day03, day04, day05, day06, day07, day08, day09, day10, day11, day12, day13,
+    day14, day15, day16, day17, day18, day19, day20, day21, day22, day23, day24, day25,
+};
+
+mod allocations;
+mod batch;
+mod isolation;
+mod report;
+
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(60);
+
+pub type Solve = fn(&str, &mut Run);
+
+pub const DAYS: [Solve; 25] = [
+    day01::solve, day02::solve, day03::solve, day04::solve, day05::solve,
+    day06::solve, day07::solve, day08::solve, day09::solve, day10::solve,
+    day11::solve, day12::solve, day13::solve, day14::solve, day15::solve,
+    day16::solve, day17::solve, day18::solve, day19::solve, day20::solve,
+    day21::solve, day22::solve, day23::solve, day24::solve, day25::solve,
+];
+
+#[derive(Default)]
+pub struct Run {
+    pub parse_duration: Duration,
+    pub parse_allocations: Option<Allocations>,
+    pub parts: Vec<Part>,
+}
+
+pub struct Part {
+    pub answer: String,
+    pub duration: Duration,
+    pub allocations: Option<Allocations>,
+}
+
+impl Run {
+    pub fn parse<T>(&mut self, parse: impl FnOnce() -> T) -> T {
+        let start = Instant::now();
+        let (parsed, allocations) = measure(parse);
+        self.parse_duration += start.elapsed();
+
+        self.parse_allocations = match (self.parse_allocations, allocations) {
+            (Some(previous), Some(allocations)) => Some(previous.combine(allocations)),
+            (previous, allocations) => previous.or(allocations),
+        };
+
+        parsed
+    }
+
+    pub fn part<T: Display>(&mut self, solve: impl FnOnce() -> T) {
+        let start = Instant::now();
+        let (answer, allocations) = measure(solve);
+        let duration = start.elapsed();
+
+        self.parts.push(Part { answer: answer.to_string(), duration, allocations });
+    }
+
+    pub fn answer(&self, part: usize) -> Option<&str> {
+        self.parts.get(part).map(|part| part.answer.as_str())
+    }
+
+    pub fn total_duration(&self) -> Duration {
+        self.parse_duration + self.parts.iter().map(|part| part.duration).sum::<Duration>()
+    }
+
+    pub fn print(&self) {
+        self.parts.iter()
+            .enumerate()
+            .for_each(|(index, part)| println!("PART {}: {}", index + 1, part.answer));
+    }
+}
+
+pub fn run(input: &str, solve: Solve) -> Run {
+    let mut run = Run::default();
+    solve(input, &mut run);
+    run
+}
+
+/// Stored answers of a day, one line per part. Empty lines mark parts without a known answer.
+pub struct Answers {
+    parts: Vec<Option<String>>,
+}
+
+impl Answers {
+    pub fn get(&self, part: usize) -> Option<&str> {
+        self.parts.get(part).and_then(|answer| answer.as_deref())
+    }
+
+    /// Returns `None` if there is no stored answer to compare against.
+    pub fn is_correct(&self, run: &Run, part: usize) -> Option<bool> {
+        self.get(part).map(|expected| run.answer(part) == Some(expected))
+    }
+}
+
+/// Directory holding one `dayXX` folder per day, each with an `input.txt` and optionally an `answers.txt`.
+pub struct Inputs {
+    root: PathBuf,
+}
+
+impl Inputs {
+    pub fn new(root: impl Into<PathBuf>) -> Inputs {
+        Inputs { root: root.into() }
+    }
+
+    /// The root as an absolute path, so that different spellings of the same directory compare equal.
+    pub fn canonical_root(&self) -> String {
+        fs::canonicalize(&self.root).unwrap_or(self.root.clone()).display().to_string()
+    }
+
+    pub fn day_directory(&self, day: usize) -> PathBuf {
+        self.root.join(format!("day{:02}", day))
+    }
+
+    pub fn input(&self, day: usize) -> Option<String> {
+        fs::read_to_string(self.day_directory(day).join("input.txt")).ok()
+    }
+
+    pub fn answers(&self, day: usize) -> Answers {
+        let content = fs::read_to_string(self.day_directory(day).join("answers.txt")).unwrap_or_default();
+
+        Answers {
+            parts: content.lines()
+                .map(|line| line.trim())
+                .map(|line| if line.is_empty() { None } else { Some(line.to_string()) })
+                .collect(),
+        }
+    }
+}
+
+impl Default for Inputs {
+    fn default() -> Self {
+        Inputs::new(Path::new(env!("CARGO_MANIFEST_DIR")).join("src"))
+    }
+}
+
+/// Command line options in the form `<positional>... --key value...`.
+pub struct Options {
+    positionals: Vec<String>,
+    values: HashMap<String, String>,
+}
+
+impl Options {
+    fn parse(args: &[String]) -> Result<Options, String> {
+        let mut positionals = vec![];
+        let mut values = HashMap::new();
+        let mut args = args.iter();
+
+        while let Some(arg) = args.next() {
+            let Some(key) = arg.strip_prefix("--") else {
+                positionals.push(arg.clone());
+                continue;
+            };
+
+            let Some(value) = args.next() else { return Err(format!("missing value for --{}", key)); };
+            values.insert(key.to_string(), value.clone());
+        }
+
+        Ok(Options { positionals, values })
+    }
+
+    pub fn positional(&self, index: usize) -> Option<&str> {
+        self.positionals.get(index).map(String::as_str)
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.values.get(key).map(String::as_str)
+    }
+
+    pub fn inputs(&self) -> Inputs {
+        self.get("inputs").map(Inputs::new).unwrap_or_default()
+    }
+
+    /// Wall-clock timeout per day, given in seconds via `--timeout`.
+    pub fn timeout(&self) -> Result<Duration, String> {
+        let Some(seconds) = self.get("timeout") else { return Ok(DEFAULT_TIMEOUT); };
+
+        seconds.parse::<f64>().ok()
+            .filter(|seconds| *seconds > 0.0)
+            .map(Duration::from_secs_f64)
+            .ok_or(format!("invalid timeout: {}", seconds))
+    }
+
+    pub fn isolation(&self) -> Result<Isolation, String> {
+        self.get("isolation").map(Isolation::from_name).unwrap_or(Ok(Isolation::default_for_profile()))
+    }
+}
+
+/// Parses a day selection like `7`, `day07` or `all`.
+fn parse_days(selection: &str) -> Result<Vec<usize>, String> {
+    if selection == "all" {
+        return Ok((1..=DAYS.len()).collect());
+    }
+
+    match selection.trim_start_matches("day").parse::<usize>() {
+        Ok(day) if (1..=DAYS.len()).contains(&day) => Ok(vec![day]),
+        _ => Err(format!("invalid day: {}", selection)),
+    }
+}
+
+pub fn main(args: &[String]) {
+    let result = Options::parse(&args[1..]).and_then(|options| match args[0].as_str() {
+        "run" => run_days(&options),
+        "report" => report::main(&options),
+        "batch" => batch::main(&options),
+        "solve" => isolation::child_main(&options),
+        "explain" => explain_day(&options),
+        "stream" => stream_day(&options),
+        command => Err(format!("unknown command: {}", command)),
+    });
+
+    if let Err(error) = result {
+        eprintln!("error: {}", error);
+        eprintln!("usage: run <day|all> [--inputs DIR] [ISOLATION OPTIONS]");
+        eprintln!("       report [--format markdown|html] [--output FILE] [--log FILE] [--inputs DIR] [ISOLATION OPTIONS]");
+        eprintln!("       batch --inputs-root DIR [ISOLATION OPTIONS]");
+        eprintln!("       explain <day> [--inputs DIR]");
+        eprintln!("       explain 7 [--operators +,*,||] [--precedence left-to-right|standard] [--inputs DIR]");
+        eprintln!("       explain 8 [--harmonics K1..K2] [--inputs DIR]");
+        eprintln!("       stream 1 [--chunk-size N] [--columns A,B] < INPUT");
+        eprintln!("isolation options: [--isolation none|thread|process] [--timeout SECONDS]");
+        process::exit(2);
+    }
+}
+
+fn run_days(options: &Options) -> Result<(), String> {
+    let days = parse_days(options.positional(0).unwrap_or("all"))?;
+    let inputs = options.inputs();
+    let isolation = options.isolation()?;
+    let timeout = options.timeout()?;
+
+    for day in days {
+        let Some(input) = inputs.input(day) else {
+            println!("DAY {:02}: no input", day);
+            continue;
+        };
+
+        let run = match isolation.run(day, &input, timeout) {
+            Outcome::Solved(run) => run,
+            Outcome::Failed(failure) => {
+                println!("DAY {:02}: {}", day, failure);
+                continue;
+            }
+        };
+
+        println!("DAY {:02} (parse {})", day, format_measurement(run.parse_duration, run.parse_allocations));
+        run.parts.iter()
+            .enumerate()
+            .for_each(|(index, part)| println!("  PART {}: {} ({})", index + 1, part.answer, format_measurement(part.duration, part.allocations)));
+    }
+
+    Ok(())
+}
+
+/// Prints a day's diagnostics, for the days that support it.
+fn explain_day(options: &Options) -> Result<(), String> {
+    let [day] = parse_days(options.positional(0).unwrap_or_default())?[..] else { return Err(String::from("explain expects a single day")); };
+    let input = options.inputs().input(day).ok_or(format!("no input for day {}", day))?;
+
+    match day {
+        1 => day01::explain(&input),
+        2 => day02::explain(&input),
+        3 => day03::explain(&input),
+        4 => day04::explain(&input),
+        5 => day05::explain(&input),
+        6 => day06::explain(&input),
+        7 => day07::explain(&input, options)?,
+        8 => day08::explain(&input, options)?,
+        9 => day09::explain(&input),
+        _ => return Err(format!("day {} has no explain mode", day)),
+    }
+
+    Ok(())
+}
+
+/// Solves a day from stdin with bounded memory, for the days that support it.
+fn stream_day(options: &Options) -> Result<(), String> {
+    let [day] = parse_days(options.positional(0).unwrap_or_default())?[..] else { return Err(String::from("stream expects a single day")); };
+
+    match day {
+        1 => day01::streaming::main(options),
+        _ => Err(format!("day {} has no stream mode", day)),
+    }
+}
+
+pub fn format_duration(duration: Duration) -> String {
+    let nanos = duration.as_nanos();
+
+    match nanos {
+        0..1_000 => format!("{} ns", nanos),
+        1_000..1_000_000 => format!("{:.1} µs", nanos as f64 / 1e3),
+        1_000_000..1_000_000_000 => format!("{:.1} ms", nanos as f64 / 1e6),
+        _ => format!("{:.2} s", nanos as f64 / 1e9),
+    }
+}
+
+pub fn format_measurement(duration: Duration, allocations: Option<Allocations>) -> String {
+    match allocations {
+        Some(allocations) => format!("{}, {}", format_duration(duration), allocations),
+        None => format_duration(duration),
+    }
+}
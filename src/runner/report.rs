@@ -0,0 +1,239 @@
+use std::collections::HashMap;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use super::isolation::Outcome;
+use super::{format_duration, format_measurement, parse_days, Answers, Options};
+
+const SPARKLINE_CHARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+const HISTORY_LENGTH: usize = 20;
+
+pub fn main(options: &Options) -> Result<(), String> {
+    let format = Format::from_name(options.get("format").unwrap_or("markdown"))?;
+    let output = options.get("output").unwrap_or(format.default_output());
+    let log = options.get("log").unwrap_or("runs.log");
+    let inputs = options.inputs();
+    let isolation = options.isolation()?;
+    let timeout = options.timeout()?;
+
+    let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+
+    let rows = parse_days("all")?.into_iter()
+        .filter_map(|day| {
+            let input = inputs.input(day)?;
+            let outcome = isolation.run(day, &input, timeout);
+            let answers = inputs.answers(day);
+
+            Some(Row { day, outcome, answers, history: vec![] })
+        })
+        .collect::<Vec<Row>>();
+
+    let inputs_root = inputs.canonical_root();
+    append_to_log(log, timestamp, &inputs_root, &rows)?;
+
+    let history = read_log(log, &inputs_root);
+    let rows = rows.into_iter()
+        .map(|row| Row { history: history.get(&row.day).cloned().unwrap_or_default(), ..row })
+        .collect::<Vec<Row>>();
+
+    fs::write(output, format.render(&rows, timestamp)).map_err(|error| format!("failed to write {}: {}", output, error))?;
+    println!("wrote report for {} days to {}", rows.len(), output);
+
+    Ok(())
+}
+
+struct Row {
+    day: usize,
+    outcome: Outcome,
+    answers: Answers,
+    history: Vec<Duration>,
+}
+
+impl Row {
+    fn cells(&self) -> [String; 8] {
+        let run = match &self.outcome {
+            Outcome::Solved(run) => run,
+            Outcome::Failed(failure) => return [
+                format!("{:02}", self.day),
+                failure.to_string(),
+                String::from("-"),
+                String::from("-"),
+                String::from("-"),
+                String::from("-"),
+                String::from("-"),
+                sparkline(&self.history),
+            ],
+        };
+
+        let answer = |part: usize| match (run.answer(part), self.answers.is_correct(run, part)) {
+            (None, _) => String::from("-"),
+            (Some(answer), Some(true)) => format!("{} ✓", answer),
+            (Some(answer), Some(false)) => format!("{} ✗", answer),
+            (Some(answer), None) => format!("{} ?", answer),
+        };
+
+        let duration = |part: usize| match run.parts.get(part) {
+            Some(part) => format_measurement(part.duration, part.allocations),
+            None => String::from("-"),
+        };
+
+        [
+            format!("{:02}", self.day),
+            answer(0),
+            answer(1),
+            format_measurement(run.parse_duration, run.parse_allocations),
+            duration(0),
+            duration(1),
+            format_duration(run.total_duration()),
+            sparkline(&self.history),
+        ]
+    }
+}
+
+const HEADERS: [&str; 8] = ["Day", "Part 1", "Part 2", "Parse", "Part 1 time", "Part 2 time", "Total", "History"];
+
+/// Each line of the run log holds `timestamp inputs_root day parse_nanos total_nanos`, separated by tabs.
+/// Days that failed are not logged.
+fn append_to_log(path: &str, timestamp: u64, inputs_root: &str, rows: &[Row]) -> Result<(), String> {
+    let mut file = OpenOptions::new().create(true).append(true).open(path)
+        .map_err(|error| format!("failed to open {}: {}", path, error))?;
+
+    rows.iter()
+        .filter_map(|row| match &row.outcome {
+            Outcome::Solved(run) => Some((row.day, run)),
+            Outcome::Failed(_) => None,
+        })
+        .try_for_each(|(day, run)| writeln!(
+            file, "{}\t{}\t{}\t{}\t{}",
+            timestamp, inputs_root, day, run.parse_duration.as_nanos(), run.total_duration().as_nanos()
+        ))
+        .map_err(|error| format!("failed to write {}: {}", path, error))
+}
+
+/// Reads the total durations per day of the runs on the given inputs root.
+fn read_log(path: &str, inputs_root: &str) -> HashMap<usize, Vec<Duration>> {
+    fs::read_to_string(path).unwrap_or_default()
+        .lines()
+        .filter_map(|line| {
+            let [_, root, day, _, total] = line.split('\t').collect::<Vec<&str>>()[..] else { return None; };
+            if root != inputs_root {
+                return None;
+            }
+
+            Some((day.parse::<usize>().ok()?, Duration::from_nanos(total.parse().ok()?)))
+        })
+        .fold(HashMap::new(), |mut history, (day, duration)| {
+            history.entry(day).or_insert(vec![]).push(duration);
+            history
+        })
+}
+
+fn sparkline(history: &[Duration]) -> String {
+    let recent = &history[history.len().saturating_sub(HISTORY_LENGTH)..];
+
+    let Some(min) = recent.iter().min() else { return String::new(); };
+    let max = recent.iter().max().unwrap();
+    let range = (*max - *min).as_secs_f64();
+
+    recent.iter()
+        .map(|duration| {
+            if range == 0.0 {
+                return SPARKLINE_CHARS[0];
+            }
+
+            let level = ((*duration - *min).as_secs_f64() / range * (SPARKLINE_CHARS.len() - 1) as f64).round();
+            SPARKLINE_CHARS[level as usize]
+        })
+        .collect()
+}
+
+enum Format {
+    Markdown,
+    Html,
+}
+
+impl Format {
+    fn from_name(name: &str) -> Result<Format, String> {
+        match name {
+            "markdown" | "md" => Ok(Format::Markdown),
+            "html" => Ok(Format::Html),
+            _ => Err(format!("unknown report format: {}", name)),
+        }
+    }
+
+    fn default_output(&self) -> &'static str {
+        match self {
+            Format::Markdown => "report.md",
+            Format::Html => "report.html",
+        }
+    }
+
+    fn render(&self, rows: &[Row], timestamp: u64) -> String {
+        match self {
+            Format::Markdown => render_markdown(rows, timestamp),
+            Format::Html => render_html(rows, timestamp),
+        }
+    }
+}
+
+fn render_markdown(rows: &[Row], timestamp: u64) -> String {
+    let mut markdown = format!("# Advent of Code 2024\n\nGenerated at {} (unix time).\n\n", timestamp);
+
+    markdown += &format!("| {} |\n", HEADERS.join(" | "));
+    markdown += &format!("|{}\n", "---|".repeat(HEADERS.len()));
+
+    rows.iter().for_each(|row| {
+        let cells = row.cells().map(|cell| cell.replace('|', "\\|"));
+        markdown += &format!("| {} |\n", cells.join(" | "));
+    });
+
+    markdown
+}
+
+fn render_html(rows: &[Row], timestamp: u64) -> String {
+    let header = HEADERS.iter().map(|header| format!("<th>{}</th>", header)).collect::<String>();
+
+    let body = rows.iter()
+        .map(|row| {
+            let cells = row.cells().iter().map(|cell| format!("<td>{}</td>", escape_html(cell))).collect::<String>();
+            format!("<tr>{}</tr>\n", cells)
+        })
+        .collect::<String>();
+
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>Advent of Code 2024</title>\n\
+        <style>body {{ font-family: sans-serif; }} table {{ border-collapse: collapse; }} \
+        th, td {{ border: 1px solid #ccc; padding: 4px 8px; text-align: right; }}</style>\n\
+        </head>\n<body>\n<h1>Advent of Code 2024</h1>\n<p>Generated at {} (unix time).</p>\n\
+        <table>\n<tr>{}</tr>\n{}</table>\n</body>\n</html>\n",
+        timestamp, header, body
+    )
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_history_of_inputs_root_only() {
+        let path = std::env::temp_dir().join(format!("runs-{}.log", std::process::id()));
+        let log = "1\t/inputs/a\t1\t10\t100\n\
+            1\t/inputs/b\t1\t10\t200\n\
+            2\t/inputs/a\t1\t10\t300\n\
+            2\t/inputs/a\t2\t10\t400\n\
+            3\t1\t10\t500\n";
+        fs::write(&path, log).unwrap();
+
+        let history = read_log(path.to_str().unwrap(), "/inputs/a");
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(history[&1], vec![Duration::from_nanos(100), Duration::from_nanos(300)]);
+        assert_eq!(history[&2], vec![Duration::from_nanos(400)]);
+        assert_eq!(history.len(), 2);
+    }
+}